@@ -35,6 +35,9 @@ extern crate arr_macro;
 #[cfg(test)]
 extern crate hex;
 
+#[cfg(all(test, feature = "serde-witness"))]
+extern crate serde_json;
+
 pub mod jubjub;
 pub mod alt_babyjubjub;
 pub mod group_hash;
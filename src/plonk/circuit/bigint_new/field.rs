@@ -14,6 +14,7 @@ use super::super::linear_combination::LinearCombination;
 use super::super::simple_term::Term;
 use crate::plonk::circuit::hashes_with_tables::utils::IdentifyFirstLast;
 use crate::plonk::circuit::SomeArithmetizable;
+use crate::bellman::pairing::ff::{SqrtField, LegendreSymbol};
 
 
 // TODO and NB: the code here is very tight and dense. Every line should be carefully reviewed and double checked
@@ -24,6 +25,28 @@ use crate::plonk::circuit::SomeArithmetizable;
 // NB: this value is chosen more or less randomly - may be it is better to add some heuristics here
 const MAX_INTERMIDIATE_OVERFLOW_WIDTH : usize = 8;
 
+// number of non-constant divisions (div, inverse, div_with_chain) performed by the current thread:
+// allows to compare strategies of curve arithmetic empirically, as divisions dominate their cost
+#[cfg(feature = "inversion-stats")]
+thread_local! {
+    static NUM_INVERSIONS: std::cell::Cell<usize> = std::cell::Cell::new(0);
+}
+
+#[cfg(feature = "inversion-stats")]
+pub fn reset_inversion_stats() {
+    NUM_INVERSIONS.with(|x| x.set(0));
+}
+
+#[cfg(feature = "inversion-stats")]
+pub fn get_num_inversions() -> usize {
+    NUM_INVERSIONS.with(|x| x.get())
+}
+
+#[cfg(feature = "inversion-stats")]
+fn increment_inversions_count() {
+    NUM_INVERSIONS.with(|x| x.set(x.get() + 1));
+}
+
 // TODO: coarsely is completely unnecessary - get rid of it everywhere!
 // There is no problem to pay for one addtional constraint on exact allocation
 // TODO: track also if value is normalized
@@ -76,6 +99,9 @@ fn construct_equality_certificate<E: Engine, CS: ConstraintSystem<E>>(
 pub struct RnsParameters<E: Engine, F: PrimeField>{
     allow_individual_limb_overflow: bool,
     allow_coarse_allocation_for_temp_values: bool,
+    // if set, higher level gadgets (e.g. elliptic curve arithmetic) normalize their outputs:
+    // this costs additional constraints but makes the reduction status of results predictable
+    auto_reduce: bool,
 
     range_check_strategy: RangeConstraintStrategy,
     range_check_granularity: usize,
@@ -108,11 +134,16 @@ pub struct RnsParameters<E: Engine, F: PrimeField>{
 
 impl<'a, E: Engine, F: PrimeField> RnsParameters<E, F>{
     pub fn new_optimal<CS: ConstraintSystem<E>>(cs: &mut CS, limb_size: usize) -> Self 
+    {
+        let strategy = get_optimal_strategy(cs);
+        Self::new_for_strategy(strategy, limb_size)
+    }
+
+    pub fn new_for_strategy(strategy: RangeConstraintStrategy, limb_size: usize) -> Self 
     {
         let allow_individual_limb_overflow = true;
         let allow_coarse_allocation_for_temp_values = true;
 
-        let strategy = get_optimal_strategy(cs);
         let range_check_granularity = strategy.get_range_check_granularity();
         assert!(limb_size % range_check_granularity == 0, "limb size is not a multiple of range check quant");
 
@@ -158,6 +189,7 @@ impl<'a, E: Engine, F: PrimeField> RnsParameters<E, F>{
         RnsParameters::<E, F> {
             allow_individual_limb_overflow,
             allow_coarse_allocation_for_temp_values,
+            auto_reduce: false,
             num_binary_limbs,
             range_check_strategy: strategy,
             range_check_granularity,
@@ -178,6 +210,98 @@ impl<'a, E: Engine, F: PrimeField> RnsParameters<E, F>{
             _marker_fr: std::marker::PhantomData::<F>
         }
     }
+
+    pub fn set_auto_reduce(&mut self, auto_reduce: bool) {
+        self.auto_reduce = auto_reduce;
+    }
+
+    pub fn auto_reduce(&self) -> bool {
+        self.auto_reduce
+    }
+
+    pub fn get_num_binary_limbs(&self) -> usize {
+        self.num_binary_limbs
+    }
+
+    pub fn get_binary_limb_width(&self) -> usize {
+        self.binary_limb_width
+    }
+
+    pub fn get_range_check_strategy(&self) -> RangeConstraintStrategy {
+        self.range_check_strategy
+    }
+
+    // all the parameters are derived from the limb layout and range check strategy, so only those are stored
+    // (together with the flags and the bitlengths of both moduli, which are used as a sanity check on load):
+    // strategy tag (u8) || chunk width (u8) || limb width (u32) || num limbs (u32) || 
+    // bitlength of F::char (u32) || bitlength of E::Fr::char (u32) || flags (u8), all integers are big-endian
+    pub fn write<W: std::io::Write>(&self, mut writer: W) -> std::io::Result<()> {
+        use byteorder::{WriteBytesExt, BigEndian};
+        let (tag, chunk_width) = match self.range_check_strategy {
+            RangeConstraintStrategy::NaiveSingleBit => (0u8, 0u8),
+            RangeConstraintStrategy::CustomTwoBitGate => (1u8, 0u8),
+            RangeConstraintStrategy::WithBitwiseOpTable(width) => (2u8, width as u8),
+        };
+        writer.write_u8(tag)?;
+        writer.write_u8(chunk_width)?;
+        writer.write_u32::<BigEndian>(self.binary_limb_width as u32)?;
+        writer.write_u32::<BigEndian>(self.num_binary_limbs as u32)?;
+        writer.write_u32::<BigEndian>(self.represented_field_modulus_bitlength as u32)?;
+        writer.write_u32::<BigEndian>(self.native_field_modulus_bitlength as u32)?;
+        let flags = (self.allow_individual_limb_overflow as u8) | 
+            ((self.allow_coarse_allocation_for_temp_values as u8) << 1) | ((self.auto_reduce as u8) << 2);
+        writer.write_u8(flags)
+    }
+
+    // the serialized layout is validated against E and F: InvalidData error is returned if it doesn't fit them
+    pub fn read<R: std::io::Read>(mut reader: R) -> std::io::Result<Self> {
+        use byteorder::{ReadBytesExt, BigEndian};
+        use std::io::{Error, ErrorKind};
+        let invalid = |msg: &str| Error::new(ErrorKind::InvalidData, msg.to_string());
+
+        let tag = reader.read_u8()?;
+        let chunk_width = reader.read_u8()? as usize;
+        let strategy = match tag {
+            0 => RangeConstraintStrategy::NaiveSingleBit,
+            1 => RangeConstraintStrategy::CustomTwoBitGate,
+            2 if chunk_width > 0 => RangeConstraintStrategy::WithBitwiseOpTable(chunk_width),
+            _ => return Err(invalid("unknown range check strategy")),
+        };
+        let limb_size = reader.read_u32::<BigEndian>()? as usize;
+        let num_binary_limbs = reader.read_u32::<BigEndian>()? as usize;
+        let represented_field_modulus_bitlength = reader.read_u32::<BigEndian>()? as usize;
+        let native_field_modulus_bitlength = reader.read_u32::<BigEndian>()? as usize;
+        let flags = reader.read_u8()?;
+        if flags >> 3 != 0 {
+            return Err(invalid("unknown flags"));
+        }
+
+        let native_field_modulus = repr_to_biguint::<E::Fr>(&E::Fr::char());
+        let represented_field_modulus = repr_to_biguint::<F>(&F::char());
+        if native_field_modulus.bits() as usize != native_field_modulus_bitlength {
+            return Err(invalid("parameters were created for another native field"));
+        }
+        if represented_field_modulus.bits() as usize != represented_field_modulus_bitlength {
+            return Err(invalid("parameters were created for another represented field"));
+        }
+        // the requirements which are asserted in new_for_strategy
+        if limb_size == 0 || limb_size % strategy.get_range_check_granularity() != 0 {
+            return Err(invalid("limb size is not a multiple of range check quant"));
+        }
+        if represented_field_modulus * 4u64 >= native_field_modulus << limb_size {
+            return Err(invalid("4 * p >= native_field_modulus * 2^limb_width"));
+        }
+
+        let mut params = Self::new_for_strategy(strategy, limb_size);
+        if params.num_binary_limbs != num_binary_limbs {
+            return Err(invalid("number of limbs is inconsistent with limb width"));
+        }
+        params.allow_individual_limb_overflow = flags & 1 != 0;
+        params.allow_coarse_allocation_for_temp_values = flags & 2 != 0;
+        params.auto_reduce = flags & 4 != 0;
+
+        Ok(params)
+    }
 }
 
 
@@ -322,18 +446,65 @@ impl<'a, E: Engine, F: PrimeField> std::fmt::Display for FieldElement<'a, E, F>
 }
 
 
+// the number of terms in the chain is bounded, so that the width of the quotient in constraint_fma
+// stays reasonable: longer chains are collapsed into a single (reduced) element before the multiplication.
+// All the chains built by the point arithmetic contain at most four terms: the point methods return their
+// coordinates as field elements rather than pending chains, so longer chains only come from the callers
+// building them explicitly (point variants handing out the chains are not provided)
+pub const DEFAULT_MAX_CHAIN_LENGTH: usize = 8;
+
 pub struct FieldElementsChain<'a, E: Engine, F: PrimeField> {
     pub elems_to_add: Vec<FieldElement<'a, E, F>>,
-    pub elems_to_sub: Vec<FieldElement<'a, E, F>> 
+    pub elems_to_sub: Vec<FieldElement<'a, E, F>>,
+    max_num_terms: usize
 }
 
 impl<'a, E: Engine, F: PrimeField> FieldElementsChain<'a, E, F> {
     pub fn new() -> Self {
+        Self::with_max_num_terms(DEFAULT_MAX_CHAIN_LENGTH)
+    }
+
+    // advanced users may allow longer chains (and hence defer reductions further) if the field permits
+    pub fn with_max_num_terms(max_num_terms: usize) -> Self {
+        assert!(max_num_terms > 0);
         FieldElementsChain::<E, F> {
             elems_to_add: vec![],
-            elems_to_sub: vec![] 
+            elems_to_sub: vec![],
+            max_num_terms
         }
     }
+
+    pub fn get_max_num_terms(&self) -> usize {
+        self.max_num_terms
+    }
+
+    pub fn len(&self) -> usize {
+        self.elems_to_add.len() + self.elems_to_sub.len()
+    }
+
+    // if the chain is longer than allowed, all the terms are summed up explicitly (each addition reduces 
+    // the result if necessary) and the chain is replaced by the single positive term; returns true iff
+    // the chain has been collapsed
+    pub fn collapse_if_necessary<CS>(&mut self, cs: &mut CS) -> Result<bool, SynthesisError> 
+    where CS: ConstraintSystem<E>
+    {
+        if self.len() <= self.max_num_terms {
+            return Ok(false);
+        }
+        let sample_elem = self.elems_to_add.get(0).unwrap_or_else(|| &self.elems_to_sub[0]);
+        let params = sample_elem.representation_params;
+        let mut acc = FieldElement::zero(params);
+        for elem in self.elems_to_add.iter() {
+            acc = acc.add(cs, elem)?;
+        }
+        for elem in self.elems_to_sub.iter() {
+            acc = acc.sub(cs, elem)?;
+        }
+        self.elems_to_add = vec![acc];
+        self.elems_to_sub = vec![];
+
+        Ok(true)
+    }
     
     pub fn add_pos_term(&mut self, elem: &FieldElement<'a, E, F>) -> &mut Self {
         self.elems_to_add.push(elem.clone());
@@ -364,10 +535,11 @@ impl<'a, E: Engine, F: PrimeField> FieldElementsChain<'a, E, F> {
     }
 
     pub fn negate(self) -> Self {
-        let FieldElementsChain { elems_to_add, elems_to_sub } = self;
+        let FieldElementsChain { elems_to_add, elems_to_sub, max_num_terms } = self;
         FieldElementsChain {
             elems_to_add: elems_to_sub,
-            elems_to_sub: elems_to_add
+            elems_to_sub: elems_to_add,
+            max_num_terms
         }
     } 
 
@@ -512,7 +684,7 @@ impl<'a, E: Engine, F: PrimeField> FieldElement<'a, E, F> {
                 },
                 (_, _) => None,
             }
-        }).map(|x| biguint_to_fe::<F>(x));
+        }).map(|x| biguint_to_fe::<F>(x % &params.represented_field_modulus));
         
         let reduction_status = if is_normalized { ReductionStatus::Normalized } else { ReductionStatus::Unreduced };
         let new = Self {
@@ -607,6 +779,25 @@ impl<'a, E: Engine, F: PrimeField> FieldElement<'a, E, F> {
         Self::alloc_impl(cs, value_as_biguint, bit_width, params, false)
     }
 
+    // imports the element from the representation received from outside of the circuit: in contrast to alloc,
+    // the repr is not assumed to be canonical, the limbs are allocated for it as is and the circuit
+    // is unsatisfiable unless repr < F::char
+    #[track_caller]
+    pub fn from_repr_checked<CS: ConstraintSystem<E>>(
+        cs: &mut CS, repr: Option<F::Repr>, params: &'a RnsParameters<E, F>
+    ) -> Result<Self, SynthesisError> {
+        let bit_width = params.represented_field_modulus_bitlength;
+        let value = repr.map(|r| repr_to_biguint::<F>(&r));
+        // the repr which is wider than the modulus doesn't even fit into the limbs
+        if value.as_ref().map(|v| v.bits() as usize > bit_width).unwrap_or(false) {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        let (mut new, _decomposition) = Self::alloc_impl(cs, value, bit_width, params, false)?;
+        new.enforce_if_normalized(cs)?;
+        new.reduction_status = ReductionStatus::Normalized;
+        Ok(new)
+    }
+
     #[track_caller]
     pub(crate) fn alloc_for_known_bitwidth<CS: ConstraintSystem<E>>(
         cs: &mut CS, value: Option<BigUint>, bit_width: usize, params: &'a RnsParameters<E, F>, coarsely: bool
@@ -710,6 +901,10 @@ impl<'a, E: Engine, F: PrimeField> FieldElement<'a, E, F> {
         self.base_field_limb.is_constant()
     }
 
+    pub fn get_reduction_status(&self) -> ReductionStatus {
+        self.reduction_status
+    }
+
     // return maximum value based on maximum limb values
     fn get_maximal_possible_stored_value(&self) -> BigUint {
         if self.is_constant() {
@@ -781,11 +976,19 @@ impl<'a, E: Engine, F: PrimeField> FieldElement<'a, E, F> {
         Ok(is_zero)
     }
 
+    // NB: selection does not do any reductions by itself: the reduction status of the result is the worst of the 
+    // statuses of the operands, so the result of selection against unreduced value will be reduced (by the next 
+    // operation that requires it) over and over again. If the same operand is selected in the loop, it should be
+    // prepared only once outside the loop via prepare_for_select.
+    // Operands allocated against different RNS parameters have incompatible limb layouts (and the result would 
+    // have to pick one set of parameters arbitrarily), so such selection is rejected with Unsatisfiable error
     #[track_caller]
     pub fn conditionally_select<CS>(cs: &mut CS, flag: &Boolean, first: &Self, second: &Self) -> Result<Self, SynthesisError> 
     where CS: ConstraintSystem<E>
     {
-        assert!(Self::check_params_equivalence(first, second));
+        if !Self::check_params_equivalence(first, second) {
+            return Err(SynthesisError::Unsatisfiable);
+        }
         match flag {
             Boolean::Constant(c) => {
                 if *c { return Ok(first.clone()) } else { return Ok(second.clone()) };
@@ -799,9 +1002,7 @@ impl<'a, E: Engine, F: PrimeField> FieldElement<'a, E, F> {
 
         for (l, r) in first.binary_limbs.iter().zip(second.binary_limbs.iter()) 
         {
-            let mut minus_b = r.term.clone();
-            minus_b.negate();
-            let a_minus_b = l.term.add(cs, &minus_b)?;
+            let a_minus_b = Self::difference_for_selection(cs, &l.term, &r.term)?;
             let n = Term::<E>::fma(cs, &flag_as_term, &a_minus_b, &r.term)?;
             let new_max = std::cmp::max(l.max_value(), r.max_value());
             let new_limb = Limb::new(n, new_max);
@@ -809,9 +1010,7 @@ impl<'a, E: Engine, F: PrimeField> FieldElement<'a, E, F> {
             new_binary_limbs.push(new_limb);
         }
 
-        let mut minus_b = second.base_field_limb.clone();
-        minus_b.negate();
-        let a_minus_b = first.base_field_limb.add(cs, &minus_b)?;
+        let a_minus_b = Self::difference_for_selection(cs, &first.base_field_limb, &second.base_field_limb)?;
         let new_base_limb = Term::<E>::fma(cs, &flag_as_term, &a_minus_b, &second.base_field_limb)?;
 
         let new_value = if let Some(f) = flag.get_value() {
@@ -836,6 +1035,139 @@ impl<'a, E: Engine, F: PrimeField> FieldElement<'a, E, F> {
         Ok(new)
     }
 
+    // hardened version of conditionally_select: booleanity of the flag is additionally enforced (flag^2 = flag),
+    // which catches malformed Booleans constructed from unchecked variables at the cost of a single gate
+    pub fn conditionally_select_with_flag_check<CS>(
+        cs: &mut CS, flag: &Boolean, first: &Self, second: &Self
+    ) -> Result<Self, SynthesisError> 
+    where CS: ConstraintSystem<E>
+    {
+        if !flag.is_constant() {
+            let flag_as_term = Term::<E>::from_boolean(flag);
+            let mut minus_flag = flag_as_term.clone();
+            minus_flag.negate();
+            let defect = Term::<E>::fma(cs, &flag_as_term, &flag_as_term, &minus_flag)?;
+            defect.enforce_equal(cs, &Term::<E>::zero())?;
+        }
+        Self::conditionally_select(cs, flag, first, second)
+    }
+
+    // reduces the operand once, so that all the subsequent selections against it are (at least) loosely reduced
+    pub fn prepare_for_select<CS: ConstraintSystem<E>>(&mut self, cs: &mut CS) -> Result<(), SynthesisError> {
+        self.reduce_loose(cs)
+    }
+
+    // selects table[index] where index = \sum bits[i] * 2^i, i.e. bits are in little-endian order;
+    // the selection tree is built limb-wise: variable limbs without coefficient and additive constant go through
+    // Num::conditionally_select, which costs a single gate for selector optimized main gate instead of two
+    // required by the generic flag * (a-b) + b, so that the full window of 2^w elements costs (2^w - 1) gates
+    // per limb; limbs coinciding in both branches (e.g. coming from the same table entry) are not selected at all
+    #[track_caller]
+    pub fn select_from_table<CS>(cs: &mut CS, bits: &[Boolean], table: &[Self]) -> Result<Self, SynthesisError>
+    where CS: ConstraintSystem<E>
+    {
+        assert!(table.len() > 0 && table.len() == 1 << bits.len(), "table size should be 2^(number of bits)");
+        if !table.iter().all(|el| Self::check_params_equivalence(el, &table[0])) {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+
+        let mut layer = table.to_vec();
+        for bit in bits.iter() {
+            let mut next_layer = Vec::with_capacity(layer.len() / 2);
+            for pair in layer.chunks(2) {
+                next_layer.push(Self::select_for_table(cs, bit, &pair[1], &pair[0])?);
+            }
+            layer = next_layer;
+        }
+
+        Ok(layer.pop().unwrap())
+    }
+
+    fn select_for_table<CS>(cs: &mut CS, flag: &Boolean, first: &Self, second: &Self) -> Result<Self, SynthesisError>
+    where CS: ConstraintSystem<E>
+    {
+        if flag.is_constant() {
+            return Self::conditionally_select(cs, flag, first, second);
+        }
+
+        let mut new_binary_limbs = Vec::with_capacity(first.binary_limbs.len());
+        for (l, r) in first.binary_limbs.iter().zip(second.binary_limbs.iter()) {
+            let n = Self::select_limb_term(cs, flag, &l.term, &r.term)?;
+            let new_max = std::cmp::max(l.max_value(), r.max_value());
+            new_binary_limbs.push(Limb::new(n, new_max));
+        }
+        let new_base_limb = Self::select_limb_term(cs, flag, &first.base_field_limb, &second.base_field_limb)?;
+
+        let new_value = if let Some(f) = flag.get_value() {
+            if f { first.get_field_value() } else { second.get_field_value() }
+        } else {
+            None
+        };
+
+        let final_reduction_status = match (first.reduction_status, second.reduction_status) {
+            (ReductionStatus::Unreduced, _) | (_, ReductionStatus::Unreduced) => ReductionStatus::Unreduced,
+            (ReductionStatus::Loose, _) | (_, ReductionStatus::Loose) => ReductionStatus::Loose,
+            (ReductionStatus::Normalized, ReductionStatus::Normalized) => ReductionStatus::Normalized
+        };
+
+        Ok(Self {
+            binary_limbs: new_binary_limbs,
+            base_field_limb: new_base_limb,
+            value: new_value,
+            representation_params: first.representation_params,
+            reduction_status: final_reduction_status
+        })
+    }
+
+    fn select_limb_term<CS>(cs: &mut CS, flag: &Boolean, a: &Term<E>, b: &Term<E>) -> Result<Term<E>, SynthesisError>
+    where CS: ConstraintSystem<E>
+    {
+        if a.circuit_eq(b) {
+            return Ok(a.clone());
+        }
+
+        // if one of the limbs is constant the generic path already costs a single gate (and none for two constants)
+        let is_plain_variable = |t: &Term<E>| {
+            !t.is_constant() && t.coeff == E::Fr::one() && t.constant_term.is_zero()
+        };
+        if is_plain_variable(a) && is_plain_variable(b) {
+            let n = Num::conditionally_select(cs, flag, &a.num, &b.num)?;
+            return Ok(Term::from_num(n));
+        }
+
+        let flag_as_term = Term::<E>::from_boolean(flag);
+        let a_minus_b = Self::difference_for_selection(cs, a, b)?;
+        Term::<E>::fma(cs, &flag_as_term, &a_minus_b, b)
+    }
+
+    // a - b for the purposes of selection: if one of the terms is constant the difference is still an affine
+    // function of the other one, so we keep it as a*X + b and let the subsequent fma absorb it;
+    // this way selection between variable and constant limb costs a single gate instead of two
+    fn difference_for_selection<CS>(cs: &mut CS, a: &Term<E>, b: &Term<E>) -> Result<Term<E>, SynthesisError>
+    where CS: ConstraintSystem<E>
+    {
+        match (a.is_constant(), b.is_constant()) {
+            (false, true) => {
+                let mut res = a.clone();
+                let mut c = b.get_constant_value();
+                c.negate();
+                res.add_constant(&c);
+                Ok(res)
+            },
+            (true, false) => {
+                let mut res = b.clone();
+                res.negate();
+                res.add_constant(&a.get_constant_value());
+                Ok(res)
+            },
+            _ => {
+                let mut minus_b = b.clone();
+                minus_b.negate();
+                a.add(cs, &minus_b)
+            }
+        }
+    }
+
     pub fn negate<CS: ConstraintSystem<E>>(&self, cs: &mut CS) -> Result<Self, SynthesisError> {
         Self::zero(&self.representation_params).sub(cs, self)
     }
@@ -846,8 +1178,9 @@ impl<'a, E: Engine, F: PrimeField> FieldElement<'a, E, F> {
     pub fn conditionally_negate<CS>(&self, cs: &mut CS, flag: &Boolean) -> Result<Self, SynthesisError> 
     where CS: ConstraintSystem<E>
     {
-        if let Some(f) = flag.get_value() {
-            if f { return self.negate(cs) } else { return Ok(self.clone()) }
+        // only constant flags may be resolved in place: for allocated ones the witness value is not a constraint
+        if let Boolean::Constant(f) = flag {
+            if *f { return self.negate(cs) } else { return Ok(self.clone()) }
         };
         let negated = self.negate(cs)?;
         Self::conditionally_select(cs, flag, &negated, self)
@@ -1128,6 +1461,58 @@ impl<'a, E: Engine, F: PrimeField> FieldElement<'a, E, F> {
         self.double_with_reduction(cs, mode)
     }
 
+    // multiplication by a (small) constant is folded into the coefficients of the limb terms the same way as 
+    // in double: no gates are required unless the growth of the limbs triggers the reduction
+    pub fn scale_by_constant_with_reduction<CS>(&self, cs: &mut CS, c: E::Fr, mode: ReductionStatus) -> Result<Self, SynthesisError> 
+    where CS: ConstraintSystem<E> 
+    {
+        let params = self.representation_params;
+        let c_as_biguint = fe_to_biguint(&c);
+        let c_in_field = biguint_to_fe::<F>(c_as_biguint.clone() % &params.represented_field_modulus);
+        let new_value = self.get_field_value().map(|x| {
+            let mut tmp = x;
+            tmp.mul_assign(&c_in_field);
+            tmp
+        });
+
+        if self.is_constant() {
+            return Ok(Self::constant(new_value.unwrap(), params));
+        }
+
+        let mut new_binary_limbs = vec![];
+        for l in self.binary_limbs.iter()
+        {
+            let mut new_term = l.term.clone();
+            new_term.scale(&c);
+            let new_max_value = l.max_value.clone() * &c_as_biguint;
+
+            let limb = Limb::<E>::new(new_term, new_max_value);
+            new_binary_limbs.push(limb);
+        }
+        let mut new_base_limb = self.base_field_limb.clone();
+        new_base_limb.scale(&c);
+
+        let mut new = Self {
+            binary_limbs: new_binary_limbs,
+            base_field_limb: new_base_limb,
+            value: new_value,
+            representation_params: params,
+            reduction_status: ReductionStatus::Unreduced
+        };
+        
+        new.reduce_if_necessary(cs, mode)?;
+        Ok(new)
+    }
+
+    pub fn scale_by_constant<CS: ConstraintSystem<E>>(&self, cs: &mut CS, c: E::Fr) -> Result<Self, SynthesisError> {
+        let mode = if self.representation_params.allow_individual_limb_overflow {
+            ReductionStatus::Unreduced
+        } else {
+            ReductionStatus::Loose
+        };
+        self.scale_by_constant_with_reduction(cs, c, mode)
+    }
+
     pub fn sub_with_reduction<CS: ConstraintSystem<E>>(
         &self, cs: &mut CS, other: &Self, mode: ReductionStatus
     ) -> Result<Self, SynthesisError> 
@@ -1229,9 +1614,10 @@ impl<'a, E: Engine, F: PrimeField> FieldElement<'a, E, F> {
     }
 
     #[track_caller]
-    pub fn div_with_chain<CS>(cs: &mut CS, chain: FieldElementsChain<'a, E, F>, den: &Self) -> Result<Self, SynthesisError> 
+    pub fn div_with_chain<CS>(cs: &mut CS, mut chain: FieldElementsChain<'a, E, F>, den: &Self) -> Result<Self, SynthesisError> 
     where CS: ConstraintSystem<E>
     {
+        chain.collapse_if_necessary(cs)?;
         let sample_elem = chain.elems_to_add.get(0).unwrap_or_else(|| chain.elems_to_sub.get(0).expect("Chain is empty"));
         let params = &sample_elem.representation_params;
         // we do chain/den = result mod p, where we assume that den != 0
@@ -1247,6 +1633,8 @@ impl<'a, E: Engine, F: PrimeField> FieldElement<'a, E, F> {
             Ok(res)
         }
         else {
+            #[cfg(feature = "inversion-stats")]
+            increment_inversions_count();
             let res = Self::alloc(cs, final_value, params)?;
             let chain = chain.negate();
             Self::constraint_fma(cs, &res, &den, chain)?;
@@ -1258,6 +1646,7 @@ impl<'a, E: Engine, F: PrimeField> FieldElement<'a, E, F> {
     pub fn mul_with_chain<CS: ConstraintSystem<E>>(
         cs: &mut CS, a: &Self, b: &Self, mut chain: FieldElementsChain<'a, E, F>,
     ) -> Result<Self, SynthesisError> {
+        chain.collapse_if_necessary(cs)?;
         let params = &a.representation_params;
         let mut final_value = a.get_field_value();
         final_value = final_value.mul(&b.get_field_value());
@@ -1313,7 +1702,43 @@ impl<'a, E: Engine, F: PrimeField> FieldElement<'a, E, F> {
         Ok(())
     }
 
+    // enforce_equal assumes that the operands are represented by the same integers, however congruent elements 
+    // with different (loosely reduced) representations, e.g. x and x + p, would violate it. Here both elements
+    // are brought into the canonical form first, so the check is precisely x = y (mod p): for the scalar field
+    // of the curve this is the equality modulo the group order
     #[track_caller]
+    pub fn enforce_equal_mod_order<CS>(&mut self, cs: &mut CS, other: &mut Self) -> Result<(), SynthesisError> 
+    where CS: ConstraintSystem<E>
+    {
+        self.normalize(cs)?;
+        other.normalize(cs)?;
+        Self::enforce_equal(cs, self, other)
+    }
+
+    #[track_caller]
+    // hard assertion that the element is equal to the given constant: the same check as in enforce_equal
+    // (the least significant limb and the residue modulo native field), but the constant side is folded
+    // directly into the gates and no reduction of the other operand is required
+    pub fn enforce_equal_to_constant<CS>(&mut self, cs: &mut CS, c: F) -> Result<(), SynthesisError> 
+    where CS: ConstraintSystem<E>
+    {
+        let constant = Self::constant(c, self.representation_params);
+        if self.is_constant() {
+            if self.get_field_value().unwrap() == c {
+                return Ok(())
+            }
+            return Err(SynthesisError::Unsatisfiable)
+        }
+
+        self.reduce_if_necessary(cs, ReductionStatus::Loose)?;
+        self.binary_limbs[0].term.enforce_equal(cs, &constant.binary_limbs[0].term)?;
+        self.base_field_limb.enforce_equal(cs, &constant.base_field_limb)?;
+        // constant is always normalized, so is the element equal to it
+        self.reduction_status = ReductionStatus::Normalized;
+
+        Ok(())
+    }
+
     pub fn enforce_not_equal<CS>(cs: &mut CS, this: &mut Self, other: &mut Self) -> Result<(), SynthesisError> 
     where CS: ConstraintSystem<E>
     {
@@ -1361,6 +1786,25 @@ impl<'a, E: Engine, F: PrimeField> FieldElement<'a, E, F> {
 
     // TODO: usage by reference here is much more better
     #[track_caller]
+    // the same check as in equals, but the constant side requires neither normalization nor collapsing into Nums
+    pub fn equals_constant<CS: ConstraintSystem<E>>(&mut self, cs: &mut CS, c: F) -> Result<Boolean, SynthesisError> {
+        if self.is_constant() {
+            return Ok(Boolean::constant(self.get_field_value().unwrap() == c));
+        }
+        let constant = Self::constant(c, self.representation_params);
+        self.normalize(cs)?;
+
+        let a = self.binary_limbs[0].term.collapse_into_num(cs)?;
+        let b = Num::Constant(constant.binary_limbs[0].term.get_constant_value());
+        let out_0 = Num::equals(cs, &a, &b)?;
+        
+        let a = self.base_field_limb.collapse_into_num(cs)?;
+        let b = Num::Constant(constant.base_field_limb.get_constant_value());
+        let out_1 = Num::equals(cs, &a, &b)?;
+
+        Boolean::and(cs, &out_0, &out_1)
+    }
+
     pub fn equals<CS: ConstraintSystem<E>>(cs: &mut CS, this: &mut Self, other: &mut Self) -> Result<Boolean, SynthesisError> {
         assert!(Self::check_params_equivalence(&this, &other));
         if this.is_constant() && other.is_constant() {
@@ -1390,6 +1834,72 @@ impl<'a, E: Engine, F: PrimeField> FieldElement<'a, E, F> {
         Ok(equals)
     }
 
+    // compares the canonical representatives of both elements as integers: the limbs of normalized elements
+    // are range checked to the limb width (the most significant one to msl_width), so starting from 
+    // the least significant limb we accumulate res = (a_i < b_i) || (a_i == b_i && res)
+    pub fn is_less_than<CS: ConstraintSystem<E>>(cs: &mut CS, this: &mut Self, other: &mut Self) -> Result<Boolean, SynthesisError> {
+        assert!(Self::check_params_equivalence(&this, &other));
+        if this.is_constant() && other.is_constant() {
+            let a = fe_to_biguint(&this.get_field_value().unwrap());
+            let b = fe_to_biguint(&other.get_field_value().unwrap());
+            return Ok(Boolean::constant(a < b));
+        }
+
+        this.normalize(cs)?;
+        other.normalize(cs)?;
+        let params = this.representation_params;
+        let this_limbs = this.get_raw_limbs_representation(cs)?;
+        let other_limbs = other.get_raw_limbs_representation(cs)?;
+
+        let mut res = Boolean::constant(false);
+        for (_is_first, is_last, (a, b)) in this_limbs.iter().zip(other_limbs.iter()).identify_first_last() {
+            let width = if is_last { params.msl_width } else { params.binary_limb_width };
+            let limb_is_less = Self::limb_is_less_than(cs, a, b, width, params.range_check_strategy)?;
+            let limb_is_equal = Num::equals(cs, a, b)?;
+            let tail = Boolean::and(cs, &limb_is_equal, &res)?;
+            res = Boolean::or(cs, &limb_is_less, &tail)?;
+        }
+
+        Ok(res)
+    }
+
+    // for a, b in [0, 2^w) we allocate the flag lt = (a < b) and range check t = b - a - 1 + 2^w * (1 - lt) 
+    // to w bits: if a < b then t = b - a - 1 is in [0, 2^w - 1), otherwise t = 2^w - (a - b + 1) is in [0, 2^w),
+    // while the wrong choice of the flag leads either to the negative t or to t >= 2^w
+    fn limb_is_less_than<CS: ConstraintSystem<E>>(
+        cs: &mut CS, a: &Num<E>, b: &Num<E>, width: usize, strategy: RangeConstraintStrategy
+    ) -> Result<Boolean, SynthesisError> {
+        if a.is_constant() && b.is_constant() {
+            let a = fe_to_biguint(&a.get_value().unwrap());
+            let b = fe_to_biguint(&b.get_value().unwrap());
+            return Ok(Boolean::constant(a < b));
+        }
+
+        let lt_value = match (a.get_value(), b.get_value()) {
+            (Some(a), Some(b)) => Some(fe_to_biguint(&a) < fe_to_biguint(&b)),
+            _ => None
+        };
+        let lt = Boolean::from(AllocatedBit::alloc(cs, lt_value)?);
+
+        let shift = biguint_to_fe::<E::Fr>(BigUint::one() << width);
+        let mut minus_shift = shift;
+        minus_shift.negate();
+        let mut minus_one = E::Fr::one();
+        minus_one.negate();
+        let mut constant = shift;
+        constant.add_assign(&minus_one);
+
+        let mut lc = LinearCombination::zero();
+        lc.add_assign_number_with_coeff(b, E::Fr::one());
+        lc.add_assign_number_with_coeff(a, minus_one);
+        lc.add_assign_boolean_with_coeff(&lt, minus_shift);
+        lc.add_assign_constant(constant);
+        let t = lc.into_num(cs)?;
+        constraint_bit_length_with_strategy(cs, &t.get_variable(), width, strategy)?;
+
+        Ok(lt)
+    }
+
     #[track_caller]
     fn constraint_fma<CS: ConstraintSystem<E>>(
         cs: &mut CS, a: &Self, b: &Self, chain: FieldElementsChain<'a, E, F>
@@ -1654,11 +2164,38 @@ impl<'a, E: Engine, F: PrimeField> FieldElement<'a, E, F> {
         Ok(())
     } 
 
+    // exactly params.num_binary_limbs limbs, the least significant one first. The representation is canonical 
+    // (and so may be hashed into transcript) only for normalized elements: limbs of unreduced ones may overflow
     pub fn get_raw_limbs_representation<CS>(&self, cs: &mut CS) -> Result<Vec<Num<E>>, SynthesisError> 
     where CS: ConstraintSystem<E> {
         self.binary_limbs.iter().map(|x| x.term.collapse_into_num(cs)).collect::<Result<Vec<_>, SynthesisError>>()
     }
 
+    // inverse of get_raw_limbs_representation for normalized elements: every limb is range checked to the 
+    // limb width and the result is enforced to be normalized, so that the representation is unique
+    #[track_caller]
+    pub fn from_raw_limbs_representation<CS: ConstraintSystem<E>>(
+        cs: &mut CS, raw_limbs: &[Num<E>], params: &'a RnsParameters<E, F>
+    ) -> Result<Self, SynthesisError> {
+        assert_eq!(raw_limbs.len(), params.num_binary_limbs, "invalid number of limbs");
+        for (_is_first, is_last, limb) in raw_limbs.iter().identify_first_last() {
+            let width = if is_last { params.msl_width } else { params.binary_limb_width };
+            match limb {
+                Num::Constant(cnst) => {
+                    if fe_to_biguint(cnst).bits() as usize > width {
+                        return Err(SynthesisError::Unsatisfiable);
+                    }
+                },
+                Num::Variable(var) => constraint_bit_length_with_strategy(cs, var, width, params.range_check_strategy)?,
+            }
+        }
+
+        let mut result = unsafe { Self::alloc_from_limbs_unchecked(cs, raw_limbs, params, false)? };
+        result.enforce_if_normalized(cs)?;
+        result.reduction_status = ReductionStatus::Normalized;
+        Ok(result)
+    }
+
     #[track_caller]
     pub fn decompose_into_binary_representation<CS>(&mut self, cs: &mut CS)-> Result<Vec<Boolean>, SynthesisError> 
     where CS: ConstraintSystem<E> {
@@ -1676,6 +2213,115 @@ impl<'a, E: Engine, F: PrimeField> FieldElement<'a, E, F> {
         Ok(binary_decomposition)
     }
     
+    // interprets the element as an integer in [0, F::char) and computes it's residue modulo T::char:
+    // used to move values between the base and scalar fields of the curve (e.g. r = x mod q in ECDSA)
+    // we require bitlength of F::char to be at most bitlength of T::char, so that normalized binary
+    // representation fits into the binary limbs of T
+    #[track_caller]
+    pub fn reinterpret_in_field<'b, T, CS>(
+        &mut self, cs: &mut CS, params: &'b RnsParameters<E, T>
+    ) -> Result<FieldElement<'b, E, T>, SynthesisError> 
+    where T: PrimeField, CS: ConstraintSystem<E> 
+    {
+        let this_params = self.representation_params;
+        assert!(
+            this_params.represented_field_modulus_bitlength <= params.represented_field_modulus_bitlength,
+            "source field modulus should not be longer than the target one"
+        );
+        if self.is_constant() {
+            let value = self.get_raw_value().unwrap() % &params.represented_field_modulus;
+            return Ok(FieldElement::constant(biguint_to_fe::<T>(value), params));
+        }
+
+        self.normalize(cs)?;
+        let bits = self.decompose_into_binary_representation(cs)?;
+        let shifts = compute_shifts::<E::Fr>();
+        let mut raw_limbs = Vec::with_capacity(params.num_binary_limbs);
+        for chunk in bits.chunks(params.binary_limb_width) {
+            let mut lc = LinearCombination::zero();
+            for (bit, shift) in chunk.iter().zip(shifts.iter()) {
+                lc.add_assign_boolean_with_coeff(bit, shift.clone());
+            }
+            raw_limbs.push(lc.into_num(cs)?);
+        }
+        // pad with zeroes so that the most significant chunk gets the bound of the most significant limb
+        raw_limbs.resize(params.num_binary_limbs, Num::Constant(E::Fr::zero()));
+
+        // the value is < F::char but may be larger than T::char: so we need to normalize the result
+        let mut result = unsafe { FieldElement::alloc_from_limbs_unchecked(cs, &raw_limbs, params, false)? };
+        result.normalize(cs)?;
+        Ok(result)
+    }
+
+    // big-endian encoding of the canonical representative: ceil(bitlength / 8) bytes, every byte is a separate Num
+    pub fn to_be_bytes<CS: ConstraintSystem<E>>(&mut self, cs: &mut CS) -> Result<Vec<Num<E>>, SynthesisError> {
+        let params = self.representation_params;
+        let num_bytes = (params.represented_field_modulus_bitlength + 7) / 8;
+        if self.is_constant() {
+            let encoding = self.get_raw_value().unwrap().to_bytes_be();
+            let mut bytes = vec![0u8; num_bytes - encoding.len()];
+            bytes.extend(encoding);
+            return Ok(bytes.into_iter().map(|x| Num::Constant(u64_to_fe::<E::Fr>(x as u64))).collect());
+        }
+
+        self.normalize(cs)?;
+        let mut bits = self.decompose_into_binary_representation(cs)?;
+        assert!(bits.len() <= num_bytes * 8);
+        bits.resize(num_bytes * 8, Boolean::constant(false));
+
+        let shifts = compute_shifts::<E::Fr>();
+        let mut bytes = Vec::with_capacity(num_bytes);
+        for chunk in bits.chunks(8).rev() {
+            let mut lc = LinearCombination::zero();
+            for (bit, shift) in chunk.iter().zip(shifts.iter()) {
+                lc.add_assign_boolean_with_coeff(bit, shift.clone());
+            }
+            bytes.push(lc.into_num(cs)?);
+        }
+
+        Ok(bytes)
+    }
+
+    // inverse of to_be_bytes: every byte is range checked and the encoded value is enforced to be canonical
+    pub fn from_be_bytes<CS: ConstraintSystem<E>>(
+        cs: &mut CS, bytes: &[Num<E>], params: &'a RnsParameters<E, F>
+    ) -> Result<Self, SynthesisError> {
+        let num_bytes = (params.represented_field_modulus_bitlength + 7) / 8;
+        if bytes.len() != num_bytes {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+
+        let mut bits = Vec::with_capacity(num_bytes * 8);
+        for byte in bytes.iter().rev() {
+            bits.extend(byte.into_bits_le(cs, Some(8))?);
+        }
+        // we don't go through Boolean::enforce_equal, which panics on unequal witnesses:
+        // non-canonical encoding should lead to unsatisfied circuit rather than abort the synthesis
+        let excess_bits = bits.split_off(params.represented_field_modulus_bitlength);
+        for bit in excess_bits.iter() {
+            match bit {
+                Boolean::Constant(flag) => if *flag { return Err(SynthesisError::Unsatisfiable) },
+                _ => bit.lc(E::Fr::one()).enforce_zero(cs)?,
+            }
+        }
+
+        let shifts = compute_shifts::<E::Fr>();
+        let mut raw_limbs = Vec::with_capacity(params.num_binary_limbs);
+        for chunk in bits.chunks(params.binary_limb_width) {
+            let mut lc = LinearCombination::zero();
+            for (bit, shift) in chunk.iter().zip(shifts.iter()) {
+                lc.add_assign_boolean_with_coeff(bit, shift.clone());
+            }
+            raw_limbs.push(lc.into_num(cs)?);
+        }
+        raw_limbs.resize(params.num_binary_limbs, Num::Constant(E::Fr::zero()));
+
+        let mut result = unsafe { Self::alloc_from_limbs_unchecked(cs, &raw_limbs, params, false)? };
+        result.enforce_if_normalized(cs)?;
+        result.reduction_status = ReductionStatus::Normalized;
+        Ok(result)
+    }
+
     // this fucction is used in elliptic curve by scalar multiplication
     #[track_caller]
     pub fn decompose_into_skewed_representation<CS: ConstraintSystem<E>>(
@@ -1780,6 +2426,249 @@ impl<'a, E: Engine, F: PrimeField> FieldElement<'a, E, F> {
 
         Ok(bits)
     }
+
+    // width-w NAF: x = /sum_{i} d_i 2^i, where every digit d_i is either zero or odd with |d_i| < 2^{w-1},
+    // and among any w consecutive digits at most one is nonzero. Every digit is returned as the pair
+    // (magnitude bits in LE order, sign), d_i = (1 - 2 * sign) * magnitude, there is one more digit
+    // than the bitlength of the modulus. 
+    // As for the skewed representation, the only property relevant for soundness is that the digits 
+    // recompose to x: we check the equality of integers via two congruences, modulo native field (with the 
+    // base field limb) and modulo 2^limb_width (with the least significant limb and a small carry), 
+    // which is justified by |/sum d_i 2^i - x| < 2^{n + w + 1} < native_field_modulus * 2^{limb_width}
+    pub fn decompose_into_wnaf_representation<CS: ConstraintSystem<E>>(
+        &mut self, cs: &mut CS, window: usize
+    ) -> Result<Vec<(Vec<Boolean>, Boolean)>, SynthesisError> 
+    {
+        assert!(window >= 2, "wNAF requires the window of at least two bits");
+        let params = self.representation_params;
+        let limb_width = params.binary_limb_width;
+        let num_digits = params.represented_field_modulus_bitlength + 1;
+        assert!(num_digits + window < E::Fr::CAPACITY as usize + limb_width);
+        assert!(limb_width + window + 2 < 127);
+        self.normalize(cs)?;
+
+        let digit_values = compute_wnaf_representation(&self.get_raw_value(), window, num_digits);
+        let is_constant = self.is_constant();
+        let alloc_bit = |cs: &mut CS, wit: Option<bool>| -> Result<Boolean, SynthesisError> {
+            if is_constant { Ok(Boolean::constant(wit.unwrap())) } else { Ok(Boolean::from(AllocatedBit::alloc(cs, wit)?)) }
+        };
+
+        let mut digits = Vec::with_capacity(num_digits);
+        for digit in digit_values.iter() {
+            let mut magnitude_bits = Vec::with_capacity(window - 1);
+            for j in 0..(window - 1) {
+                let bit = digit.map(|d| (d.abs() >> j) & 1 == 1);
+                magnitude_bits.push(alloc_bit(cs, bit)?);
+            }
+            let sign = alloc_bit(cs, digit.map(|d| d < 0))?;
+            digits.push((magnitude_bits, sign));
+        }
+        if is_constant {
+            return Ok(digits);
+        }
+
+        let mut minus_one = E::Fr::one();
+        minus_one.negate();
+        let two = u64_to_fe::<E::Fr>(2);
+        let mut minus_two = two;
+        minus_two.negate();
+
+        // d_i 2^i = /sum_j 2^{i + j} (m_ij - 2 * sign_i * m_ij)
+        let mut full_lc = LinearCombination::zero();
+        let mut low_lc = LinearCombination::zero();
+        let mut shift = E::Fr::one();
+        for (i, (magnitude_bits, sign)) in digits.iter().enumerate() {
+            let mut bit_shift = shift;
+            for m in magnitude_bits.iter() {
+                let product = Boolean::and(cs, sign, m)?;
+                let mut product_coeff = bit_shift;
+                product_coeff.mul_assign(&minus_two);
+                full_lc.add_assign_boolean_with_coeff(m, bit_shift);
+                full_lc.add_assign_boolean_with_coeff(&product, product_coeff);
+                if i < limb_width {
+                    low_lc.add_assign_boolean_with_coeff(m, bit_shift);
+                    low_lc.add_assign_boolean_with_coeff(&product, product_coeff);
+                }
+                bit_shift.mul_assign(&two);
+            }
+            shift.mul_assign(&two);
+        }
+        full_lc.add_assign_term_with_coeff(&self.base_field_limb, minus_one);
+        full_lc.enforce_zero(cs)?;
+
+        // carry c = (/sum_{i < limb_width} d_i 2^i - x_0) / 2^limb_width lies in (-2^w, 2^w)
+        let low_value = digit_values.iter().take(limb_width).enumerate().try_fold(0i128, |acc, (i, d)| {
+            d.map(|d| acc + ((d as i128) << i))
+        });
+        let limb_value = self.binary_limbs[0].term.get_value().map(|x| {
+            fe_to_biguint(&x).to_u64_digits().iter().rev().fold(0i128, |acc, d| (acc << 64) + (*d as i128))
+        });
+        let carry_offset = 1i128 << window;
+        let carry = match (low_value, limb_value) {
+            (Some(low), Some(limb)) => {
+                let diff = low - limb;
+                assert!(diff % (1i128 << limb_width) == 0);
+                Some(diff / (1i128 << limb_width) + carry_offset)
+            },
+            _ => None
+        };
+        let mut carry_shift = shift_by_limb_width::<E>(limb_width);
+        carry_shift.negate();
+        for j in 0..(window + 1) {
+            let bit = alloc_bit(cs, carry.map(|c| (c >> j) & 1 == 1))?;
+            low_lc.add_assign_boolean_with_coeff(&bit, carry_shift);
+            carry_shift.mul_assign(&two);
+        }
+        let mut offset = shift_by_limb_width::<E>(limb_width);
+        offset.mul_assign(&u64_to_fe::<E::Fr>(carry_offset as u64));
+        low_lc.add_assign_constant(offset);
+        low_lc.add_assign_term_with_coeff(&self.binary_limbs[0].term, minus_one);
+        low_lc.enforce_zero(cs)?;
+
+        Ok(digits)
+    }
+}
+
+
+impl<'a, E: Engine, F: PrimeField + SqrtField> FieldElement<'a, E, F> {
+    // returns the flag of x being a quadratic residue and the witnessed root r constrained by 
+    // r^2 = flag ? x : n * x, where n is the (nonresidue) multiplicative generator of the field: 
+    // exactly one of x and n * x is a residue unless x is zero, in which case the flag is enforced to be true.
+    // For the nonresidue the root is meaningless (it is the root of n * x), the sign of the root is arbitrary
+    #[track_caller]
+    pub fn sqrt<CS: ConstraintSystem<E>>(&mut self, cs: &mut CS) -> Result<(Boolean, Self), SynthesisError> {
+        self.sqrt_impl(cs, None)
+    }
+
+    // the same as sqrt, but the canonical representative of the root is additionally enforced to be odd
+    // if is_odd is true and even otherwise, e.g. as required by point decompression. NB: the only root of
+    // zero is even, so requesting an odd root of zero leads to unsatisfied circuit
+    #[track_caller]
+    pub fn sqrt_with_parity<CS: ConstraintSystem<E>>(
+        &mut self, cs: &mut CS, is_odd: &Boolean
+    ) -> Result<(Boolean, Self), SynthesisError> {
+        self.sqrt_impl(cs, Some(is_odd))
+    }
+
+    fn sqrt_impl<CS: ConstraintSystem<E>>(
+        &mut self, cs: &mut CS, is_odd: Option<&Boolean>
+    ) -> Result<(Boolean, Self), SynthesisError> {
+        let params = self.representation_params;
+        let non_residue = F::multiplicative_generator();
+
+        let root_value = |x: F, is_odd: Option<bool>| -> (bool, F) {
+            let is_residue = x.legendre() != LegendreSymbol::QuadraticNonResidue;
+            let mut target = x;
+            if !is_residue {
+                target.mul_assign(&non_residue);
+            }
+            let mut root = target.sqrt().expect("either x or n * x is a quadratic residue");
+            if let Some(is_odd) = is_odd {
+                if root.into_repr().is_odd() != is_odd {
+                    root.negate();
+                }
+            }
+            (is_residue, root)
+        };
+
+        if self.is_constant() && is_odd.map(|b| b.is_constant()).unwrap_or(true) {
+            let (is_residue, root) = root_value(self.get_field_value().unwrap(), is_odd.map(|b| b.get_value().unwrap()));
+            if let Some(is_odd) = is_odd {
+                if root.into_repr().is_odd() != is_odd.get_value().unwrap() {
+                    return Err(SynthesisError::Unsatisfiable);
+                }
+            }
+            return Ok((Boolean::constant(is_residue), Self::constant(root, params)));
+        }
+
+        let witness = match (self.get_field_value(), is_odd.map(|b| b.get_value())) {
+            (Some(x), None) => Some(root_value(x, None)),
+            (Some(x), Some(Some(is_odd))) => Some(root_value(x, Some(is_odd))),
+            _ => None
+        };
+        let is_residue = Boolean::Is(AllocatedBit::alloc(cs, witness.map(|w| w.0))?);
+        let mut root = Self::alloc(cs, witness.map(|w| w.1), params)?;
+
+        let shifted = self.mul(cs, &Self::constant(non_residue, params))?;
+        let mut target = Self::conditionally_select(cs, &is_residue, self, &shifted)?;
+        let mut root_squared = root.square(cs)?;
+        Self::enforce_equal(cs, &mut root_squared, &mut target)?;
+
+        // zero is a residue, but prover could have claimed otherwise as n * 0 = 0
+        let is_zero = self.is_zero(cs)?;
+        let is_zero_and_non_residue = Boolean::and(cs, &is_zero, &is_residue.not())?;
+        match is_zero_and_non_residue {
+            Boolean::Constant(flag) => if flag { return Err(SynthesisError::Unsatisfiable) },
+            ref flag => flag.lc(E::Fr::one()).enforce_zero(cs)?,
+        }
+
+        if let Some(is_odd) = is_odd {
+            root.enforce_parity(cs, is_odd)?;
+        }
+
+        Ok((is_residue, root))
+    }
+
+    // limb_0 = 2 * q + is_odd with q range checked to limb_width - 1 bits: for normalized element this is the parity
+    // of the canonical representative
+    fn enforce_parity<CS: ConstraintSystem<E>>(&mut self, cs: &mut CS, is_odd: &Boolean) -> Result<(), SynthesisError> {
+        self.normalize(cs)?;
+        let params = self.representation_params;
+        let lowest_limb = self.binary_limbs[0].term.collapse_into_num(cs)?;
+
+        let quotient_value = lowest_limb.get_value().map(|x| biguint_to_fe::<E::Fr>(fe_to_biguint(&x) >> 1));
+        let quotient = AllocatedNum::alloc(cs, || quotient_value.grab())?;
+        constraint_bit_length_with_strategy(cs, &quotient, params.binary_limb_width - 1, params.range_check_strategy)?;
+
+        let mut minus_one = E::Fr::one();
+        minus_one.negate();
+        let mut minus_two = minus_one;
+        minus_two.double();
+        let mut lc = LinearCombination::zero();
+        lc.add_assign_number_with_coeff(&lowest_limb, E::Fr::one());
+        lc.add_assign_variable_with_coeff(&quotient, minus_two);
+        lc.add_assign_boolean_with_coeff(is_odd, minus_one);
+        lc.enforce_zero(cs)
+    }
+}
+
+fn shift_by_limb_width<E: Engine>(limb_width: usize) -> E::Fr {
+    let two = u64_to_fe::<E::Fr>(2);
+    let mut res = E::Fr::one();
+    for _ in 0..limb_width {
+        res.mul_assign(&two);
+    }
+    res
+}
+
+// width-w NAF digits of the value (least significant first), padded with zeroes up to num_digits
+fn compute_wnaf_representation(value: &Option<BigUint>, window: usize, num_digits: usize) -> Vec<Option<i64>> {
+    if value.is_none() {
+        return vec![None; num_digits];
+    }
+    let mut k = value.clone().unwrap();
+    let modulus = BigUint::from(1u64 << window);
+    let half = 1i64 << (window - 1);
+    let mut digits = Vec::with_capacity(num_digits);
+    while !k.is_zero() {
+        if k.is_odd() {
+            let residue = (&k % &modulus).to_u64_digits()[0] as i64;
+            let digit = if residue >= half { residue - (1i64 << window) } else { residue };
+            if digit > 0 {
+                k -= BigUint::from(digit as u64);
+            } else {
+                k += BigUint::from((-digit) as u64);
+            }
+            digits.push(Some(digit));
+        } else {
+            digits.push(Some(0));
+        }
+        k >>= 1;
+    }
+    assert!(digits.len() <= num_digits);
+    digits.resize(num_digits, Some(0));
+
+    digits
 }
 
 
@@ -2073,10 +2962,339 @@ mod test {
         let mut a = FieldElement::alloc(&mut cs, Some(a), &params).unwrap();
         a.normalize(&mut cs).unwrap();
         assert!(cs.is_satisfied());
-    }   
-}
+    }
 
+    #[test]
+    fn test_scale_by_constant() {
+        let mut cs = TrivialAssembly::<Bn256, Width4WithCustomGates, SelectorOptimizedWidth4MainGateWithDNext>::new();
+        inscribe_default_bitop_range_table(&mut cs).unwrap();
+        let params = RnsParameters::<Bn256, Fq>::new_optimal(&mut cs, 80usize);
+        let mut rng = rand::thread_rng();
 
+        let a_f: Fq = rng.gen();
+        let a = FieldElement::alloc(&mut cs, Some(a_f), &params).unwrap();
+        let mut scaled = a.scale_by_constant(&mut cs, Fr::from_str("9").unwrap()).unwrap();
+        let mut expected = a.mul(&mut cs, &FieldElement::constant(Fq::from_str("9").unwrap(), &params)).unwrap();
 
+        let mut expected_value = a_f;
+        expected_value.mul_assign(&Fq::from_str("9").unwrap());
+        assert_eq!(scaled.get_field_value().unwrap(), expected_value);
+        FieldElement::enforce_equal(&mut cs, &mut scaled, &mut expected).unwrap();
+        assert!(cs.is_satisfied());
+    }
 
-    
+    #[test]
+    fn test_chain_collapse_on_exceeding_max_num_terms() {
+        let mut cs = TrivialAssembly::<Bn256, Width4WithCustomGates, SelectorOptimizedWidth4MainGateWithDNext>::new();
+        inscribe_default_bitop_range_table(&mut cs).unwrap();
+        let params = RnsParameters::<Bn256, Fq>::new_optimal(&mut cs, 80usize);
+        let mut rng = rand::thread_rng();
+
+        let a_f: Fq = rng.gen();
+        let b_f: Fq = rng.gen();
+        let a = FieldElement::alloc(&mut cs, Some(a_f), &params).unwrap();
+        let b = FieldElement::alloc(&mut cs, Some(b_f), &params).unwrap();
+        let mut terms = vec![];
+        for _ in 0..4 {
+            let t: Fq = rng.gen();
+            terms.push((t, FieldElement::alloc(&mut cs, Some(t), &params).unwrap()));
+        }
+
+        let mut expected = a_f;
+        expected.mul_assign(&b_f);
+        for (i, (t, _)) in terms.iter().enumerate() {
+            if i % 2 == 0 { expected.add_assign(t) } else { expected.sub_assign(t) }
+        }
+
+        for &max_num_terms in [DEFAULT_MAX_CHAIN_LENGTH, 2].iter() {
+            let mut chain = FieldElementsChain::with_max_num_terms(max_num_terms);
+            for (i, (_, t)) in terms.iter().enumerate() {
+                if i % 2 == 0 { chain.add_pos_term(t); } else { chain.add_neg_term(t); }
+            }
+            assert_eq!(chain.get_max_num_terms(), max_num_terms);
+            assert_eq!(chain.len(), 4);
+
+            let mut probe = FieldElementsChain::with_max_num_terms(max_num_terms);
+            for (i, (_, t)) in terms.iter().enumerate() {
+                if i % 2 == 0 { probe.add_pos_term(t); } else { probe.add_neg_term(t); }
+            }
+            let collapsed = probe.collapse_if_necessary(&mut cs).unwrap();
+            assert_eq!(collapsed, max_num_terms < 4);
+            assert_eq!(probe.len(), if collapsed { 1 } else { 4 });
+            assert_eq!(probe.get_field_value(), chain.get_field_value());
+
+            let res = FieldElement::mul_with_chain(&mut cs, &a, &b, chain).unwrap();
+            assert_eq!(res.get_field_value().unwrap(), expected);
+        }
+        assert!(cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_wnaf_decomposition() {
+        let mut cs = TrivialAssembly::<Bn256, Width4WithCustomGates, SelectorOptimizedWidth4MainGateWithDNext>::new();
+        inscribe_default_bitop_range_table(&mut cs).unwrap();
+        let params = RnsParameters::<Bn256, Fr>::new_optimal(&mut cs, 80usize);
+        let mut rng = rand::thread_rng();
+
+        for window in 2..6 {
+            let a_f: Fr = rng.gen();
+            let mut a = FieldElement::alloc(&mut cs, Some(a_f), &params).unwrap();
+            let digits = a.decompose_into_wnaf_representation(&mut cs, window).unwrap();
+
+            // out-of-circuit reference: recomposition and the defining properties of the wNAF
+            let values: Vec<i64> = digits.iter().map(|(magnitude_bits, sign)| {
+                let magnitude = magnitude_bits.iter().rev().fold(0i64, |acc, b| 2 * acc + b.get_value().unwrap() as i64);
+                if sign.get_value().unwrap() { -magnitude } else { magnitude }
+            }).collect();
+            let mut recomposed = Fr::zero();
+            for d in values.iter().rev() {
+                recomposed.double();
+                let mut digit = u64_to_fe::<Fr>(d.abs() as u64);
+                if *d < 0 { digit.negate() };
+                recomposed.add_assign(&digit);
+            }
+            assert_eq!(recomposed, a_f);
+            assert!(values.iter().all(|d| *d == 0 || (d.abs() % 2 == 1 && d.abs() < (1 << (window - 1)))));
+            for w in values.windows(window) {
+                assert!(w.iter().filter(|d| **d != 0).count() <= 1);
+            }
+        }
+        assert!(cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_from_repr_checked() {
+        let mut rng = rand::thread_rng();
+        let a: Fq = rng.gen();
+        // p + 1 has the same bitlength as p, while 2^255 + 1 doesn't fit into the limbs
+        let mut non_canonical = Fq::char();
+        non_canonical.add_nocarry(&Fq::one().into_repr());
+        let mut too_wide = Fq::one().into_repr();
+        too_wide.as_mut()[3] |= 1u64 << 63;
+
+        let mut cs = TrivialAssembly::<Bn256, Width4WithCustomGates, SelectorOptimizedWidth4MainGateWithDNext>::new();
+        inscribe_default_bitop_range_table(&mut cs).unwrap();
+        let params = RnsParameters::<Bn256, Fq>::new_optimal(&mut cs, 80usize);
+        let elem = FieldElement::from_repr_checked(&mut cs, Some(a.into_repr()), &params).unwrap();
+        assert_eq!(elem.get_field_value(), Some(a));
+        assert!(FieldElement::from_repr_checked(&mut cs, Some(too_wide), &params).is_err());
+        assert!(cs.is_satisfied());
+
+        let mut cs = TrivialAssembly::<Bn256, Width4WithCustomGates, SelectorOptimizedWidth4MainGateWithDNext>::new();
+        inscribe_default_bitop_range_table(&mut cs).unwrap();
+        let params = RnsParameters::<Bn256, Fq>::new_optimal(&mut cs, 80usize);
+        let _ = FieldElement::from_repr_checked(&mut cs, Some(non_canonical), &params).unwrap();
+        assert!(!cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_enforce_equal_mod_order() {
+        let mut cs = TrivialAssembly::<Bn256, Width4WithCustomGates, SelectorOptimizedWidth4MainGateWithDNext>::new();
+        inscribe_default_bitop_range_table(&mut cs).unwrap();
+        let params = RnsParameters::<Bn256, Fr>::new_optimal(&mut cs, 80usize);
+        let mut rng = rand::thread_rng();
+
+        let a_f: Fr = rng.gen();
+        let b_f: Fr = rng.gen();
+        let mut a = FieldElement::alloc(&mut cs, Some(a_f), &params).unwrap();
+        let b = FieldElement::alloc(&mut cs, Some(b_f), &params).unwrap();
+        // (a + b) - b is congruent to a, but without reductions subtraction leaves it represented by a + k * p
+        let mut derived = a.add_with_reduction(&mut cs, &b, ReductionStatus::Unreduced).unwrap()
+            .sub_with_reduction(&mut cs, &b, ReductionStatus::Unreduced).unwrap();
+        assert_ne!(a.get_raw_value(), derived.get_raw_value());
+        assert_eq!(a.get_field_value(), derived.get_field_value());
+
+        a.enforce_equal_mod_order(&mut cs, &mut derived).unwrap();
+        assert!(cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_select_with_flag_check() {
+        let mut cs = TrivialAssembly::<Bn256, Width4WithCustomGates, SelectorOptimizedWidth4MainGateWithDNext>::new();
+        inscribe_default_bitop_range_table(&mut cs).unwrap();
+        let params = RnsParameters::<Bn256, Fq>::new_optimal(&mut cs, 80usize);
+        let mut rng = rand::thread_rng();
+
+        let a_f: Fq = rng.gen();
+        let b_f: Fq = rng.gen();
+        let a = FieldElement::alloc(&mut cs, Some(a_f), &params).unwrap();
+        let b = FieldElement::alloc(&mut cs, Some(b_f), &params).unwrap();
+
+        // a proper flag passes the check
+        let flag = Boolean::from(AllocatedBit::alloc(&mut cs, Some(true)).unwrap());
+        let res = FieldElement::conditionally_select_with_flag_check(&mut cs, &flag, &a, &b).unwrap();
+        assert_eq!(res.get_field_value().unwrap(), a_f);
+        assert!(cs.is_satisfied());
+
+        // malformed flag: the underlying variable holds 2
+        let two = AllocatedNum::alloc(&mut cs, || Ok(u64_to_fe::<Fr>(2))).unwrap();
+        let malformed = Boolean::from(AllocatedBit { variable: two.get_variable(), value: Some(true) });
+        let _ = FieldElement::conditionally_select_with_flag_check(&mut cs, &malformed, &a, &b).unwrap();
+        assert!(!cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_select_with_constant_operand() {
+        let mut cs = TrivialAssembly::<Bn256, Width4WithCustomGates, SelectorOptimizedWidth4MainGateWithDNext>::new();
+        inscribe_default_bitop_range_table(&mut cs).unwrap();
+        let params = RnsParameters::<Bn256, Fq>::new_optimal(&mut cs, 80usize);
+        let mut rng = rand::thread_rng();
+
+        let a_f: Fq = rng.gen();
+        let b_f: Fq = rng.gen();
+        let c_f: Fq = rng.gen();
+        let a = FieldElement::alloc(&mut cs, Some(a_f), &params).unwrap();
+        let b = FieldElement::alloc(&mut cs, Some(b_f), &params).unwrap();
+        let c = FieldElement::constant(c_f, &params);
+        let flag = Boolean::from(AllocatedBit::alloc(&mut cs, Some(true)).unwrap());
+
+        let counter_start = cs.get_current_step_number();
+        let res = FieldElement::conditionally_select(&mut cs, &flag, &a, &b).unwrap();
+        let variable_select_cost = cs.get_current_step_number() - counter_start;
+        assert_eq!(res.get_field_value().unwrap(), a_f);
+
+        let counter_start = cs.get_current_step_number();
+        let res = FieldElement::conditionally_select(&mut cs, &flag, &a, &c).unwrap();
+        let mixed_select_cost = cs.get_current_step_number() - counter_start;
+        assert_eq!(res.get_field_value().unwrap(), a_f);
+
+        let counter_start = cs.get_current_step_number();
+        let res = FieldElement::conditionally_select(&mut cs, &flag.not(), &c, &b).unwrap();
+        let mixed_select_cost_rev = cs.get_current_step_number() - counter_start;
+        assert_eq!(res.get_field_value().unwrap(), b_f);
+
+        println!("select: variable operands {}, mixed operands {} and {}", 
+            variable_select_cost, mixed_select_cost, mixed_select_cost_rev
+        );
+        assert!(mixed_select_cost < variable_select_cost);
+        assert!(mixed_select_cost_rev < variable_select_cost);
+        // one gate per binary limb and one for the native limb
+        assert_eq!(mixed_select_cost, params.num_binary_limbs + 1);
+        assert!(cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_select_against_prepared_operand() {
+        let mut cs = TrivialAssembly::<Bn256, Width4WithCustomGates, SelectorOptimizedWidth4MainGateWithDNext>::new();
+        inscribe_default_bitop_range_table(&mut cs).unwrap();
+        let params = RnsParameters::<Bn256, Fq>::new_optimal(&mut cs, 80usize);
+        let mut rng = rand::thread_rng();
+        const NUM_ITERATIONS: usize = 16;
+
+        let y_f: Fq = rng.gen();
+        let y = FieldElement::alloc(&mut cs, Some(y_f), &params).unwrap();
+        let flags : Vec<Boolean> = (0..NUM_ITERATIONS).map(|_| {
+            Boolean::from(AllocatedBit::alloc(&mut cs, Some(rng.gen())).unwrap())
+        }).collect();
+
+        let minus_y = y.negate(&mut cs).unwrap();
+        assert_eq!(minus_y.reduction_status, ReductionStatus::Unreduced);
+        let counter_start = cs.get_current_step_number();
+        for flag in flags.iter() {
+            let mut selected = FieldElement::conditionally_select(&mut cs, flag, &minus_y, &y).unwrap();
+            selected.reduce_loose(&mut cs).unwrap();
+        }
+        let unprepared_cost = cs.get_current_step_number() - counter_start;
+
+        let counter_start = cs.get_current_step_number();
+        let mut minus_y = y.negate(&mut cs).unwrap();
+        minus_y.prepare_for_select(&mut cs).unwrap();
+        for flag in flags.iter() {
+            let mut selected = FieldElement::conditionally_select(&mut cs, flag, &minus_y, &y).unwrap();
+            selected.reduce_loose(&mut cs).unwrap();
+        }
+        let prepared_cost = cs.get_current_step_number() - counter_start;
+
+        println!("{} selections: {} gates unprepared, {} gates prepared", NUM_ITERATIONS, unprepared_cost, prepared_cost);
+        assert!(prepared_cost < unprepared_cost);
+        assert!(cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_sqrt_with_existence_flag_and_parity() {
+        let mut cs = TrivialAssembly::<Bn256, Width4WithCustomGates, SelectorOptimizedWidth4MainGateWithDNext>::new();
+        inscribe_default_bitop_range_table(&mut cs).unwrap();
+        let params = RnsParameters::<Bn256, Fq>::new_optimal(&mut cs, 80usize);
+        let mut rng = rand::thread_rng();
+
+        let r_f: Fq = rng.gen();
+        let mut residue_f = r_f;
+        residue_f.square();
+        let mut non_residue_f = residue_f;
+        non_residue_f.mul_assign(&Fq::multiplicative_generator());
+
+        let mut residue = FieldElement::alloc(&mut cs, Some(residue_f), &params).unwrap();
+        let (flag, root) = residue.sqrt(&mut cs).unwrap();
+        assert_eq!(flag.get_value(), Some(true));
+        let mut root_squared = root.square(&mut cs).unwrap();
+        FieldElement::enforce_equal(&mut cs, &mut root_squared, &mut residue).unwrap();
+
+        for is_odd in vec![false, true].into_iter() {
+            let parity = Boolean::from(AllocatedBit::alloc(&mut cs, Some(is_odd)).unwrap());
+            let (flag, root) = residue.sqrt_with_parity(&mut cs, &parity).unwrap();
+            assert_eq!(flag.get_value(), Some(true));
+            assert_eq!(root.get_field_value().unwrap().into_repr().is_odd(), is_odd);
+        }
+
+        let mut non_residue = FieldElement::alloc(&mut cs, Some(non_residue_f), &params).unwrap();
+        let (flag, _) = non_residue.sqrt(&mut cs).unwrap();
+        assert_eq!(flag.get_value(), Some(false));
+
+        let mut zero = FieldElement::alloc(&mut cs, Some(Fq::zero()), &params).unwrap();
+        let (flag, root) = zero.sqrt(&mut cs).unwrap();
+        assert_eq!(flag.get_value(), Some(true));
+        assert_eq!(root.get_field_value(), Some(Fq::zero()));
+        assert!(cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_select_rejects_mismatched_params() {
+        let mut cs = TrivialAssembly::<Bn256, Width4WithCustomGates, SelectorOptimizedWidth4MainGateWithDNext>::new();
+        inscribe_default_bitop_range_table(&mut cs).unwrap();
+        let params = RnsParameters::<Bn256, Fq>::new_optimal(&mut cs, 80usize);
+        let other_params = RnsParameters::<Bn256, Fq>::new_optimal(&mut cs, 64usize);
+        let mut rng = rand::thread_rng();
+
+        let a = FieldElement::alloc(&mut cs, Some(rng.gen()), &params).unwrap();
+        let b = FieldElement::alloc(&mut cs, Some(rng.gen()), &params).unwrap();
+        let c = FieldElement::alloc(&mut cs, Some(rng.gen()), &other_params).unwrap();
+        let flag = Boolean::from(AllocatedBit::alloc(&mut cs, Some(rng.gen())).unwrap());
+
+        assert!(FieldElement::conditionally_select(&mut cs, &flag, &a, &b).is_ok());
+        // the check doesn't depend on the flag being constant
+        for flag in vec![flag, Boolean::constant(true), Boolean::constant(false)].iter() {
+            assert!(matches!(
+                FieldElement::conditionally_select(&mut cs, flag, &a, &c), Err(SynthesisError::Unsatisfiable)
+            ));
+            assert!(FieldElement::conditionally_select(&mut cs, flag, &c, &a).is_err());
+        }
+        assert!(cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_from_be_bytes_rejects_excess_bits() {
+        use crate::plonk::circuit::hashes_with_tables::utils::u64_to_ff;
+
+        let mut cs = TrivialAssembly::<Bn256, Width4WithCustomGates, SelectorOptimizedWidth4MainGateWithDNext>::new();
+        inscribe_default_bitop_range_table(&mut cs).unwrap();
+        let params = RnsParameters::<Bn256, Fq>::new_optimal(&mut cs, 80usize);
+
+        // the modulus is 254 bits long: 0x40 in the most significant byte sets the excess bit 254,
+        // while the remaining bits encode one, which is canonical by itself
+        let mut encoding = vec![0u64; 32];
+        encoding[31] = 1;
+        let mut bytes: Vec<Num<Bn256>> = encoding.iter().map(|x| {
+            Num::Variable(AllocatedNum::alloc(&mut cs, || Ok(u64_to_ff::<Fr>(*x))).unwrap())
+        }).collect();
+        let one = FieldElement::from_be_bytes(&mut cs, &bytes, &params).unwrap();
+        assert_eq!(one.get_field_value(), Some(Fq::one()));
+        assert!(cs.is_satisfied());
+
+        bytes[0] = Num::Variable(AllocatedNum::alloc(&mut cs, || Ok(u64_to_ff::<Fr>(0x40))).unwrap());
+        let _ = FieldElement::from_be_bytes(&mut cs, &bytes, &params).unwrap();
+        assert!(!cs.is_satisfied());
+
+        bytes[0] = Num::Constant(u64_to_ff::<Fr>(0x40));
+        assert!(FieldElement::from_be_bytes(&mut cs, &bytes, &params).is_err());
+    }
+}
@@ -31,6 +31,11 @@ pub mod field;
 pub use self::amplified_linear_combination::*;
 pub use self::field::*;
 
+#[cfg(feature = "serde-witness")]
+pub mod witness;
+#[cfg(feature = "serde-witness")]
+pub use self::witness::*;
+
 
 pub const BITWISE_LOGICAL_OPS_TABLE_NAME: &'static str = "Table for bitwise logical ops";
 pub const DEFAULT_RANGE_TABLE_GRANULARITY: usize = 8;
@@ -53,10 +53,27 @@ pub enum DecompositionType<E: Engine> {
 pub struct RangeCheckDecomposition<E: Engine>
 {
     chunks_bitlength: usize,
-    decomposition: DecompositionType<E>
+    decomposition: DecompositionType<E>,
+    // cost accounting: how many range checked values (limbs) are combined in this decomposition,
+    // the total number of bits they are constrained to and the number of gates spent on the checks
+    num_limbs: usize,
+    num_bits: usize,
+    num_gates: usize,
 }
 
 impl<E: Engine> RangeCheckDecomposition<E> {
+    pub fn get_num_limbs(&self) -> usize {
+        self.num_limbs
+    }
+
+    pub fn get_num_bits(&self) -> usize {
+        self.num_bits
+    }
+
+    pub fn get_num_gates(&self) -> usize {
+        self.num_gates
+    }
+
     pub fn get_chunk_bitlen(&self) -> usize {
         self.chunks_bitlength
     }
@@ -126,7 +143,10 @@ impl<E: Engine> RangeCheckDecomposition<E> {
         RangeCheckDecomposition::<E>
         {
             chunks_bitlength: separate_decompositions[0].chunks_bitlength,
-            decomposition: total_decomposition
+            decomposition: total_decomposition,
+            num_limbs: separate_decompositions.iter().map(|x| x.num_limbs).sum(),
+            num_bits: separate_decompositions.iter().map(|x| x.num_bits).sum(),
+            num_gates: separate_decompositions.iter().map(|x| x.num_gates).sum(),
         }
     }
 }
@@ -229,6 +249,7 @@ pub fn enforce_range_check_using_naive_approach<E: Engine, CS: ConstraintSystem<
         _ => (num_bits + 2) / 3 - 2, 
     };
     increment_total_gates_count(lc_gates);
+    let num_gates = num_bits + lc_gates;
 
     let has_value = var.get_value().is_some();
     let value = var.get_value().unwrap_or(E::Fr::zero());
@@ -323,6 +344,9 @@ pub fn enforce_range_check_using_naive_approach<E: Engine, CS: ConstraintSystem<
     Ok(RangeCheckDecomposition {
         chunks_bitlength: 1,
         decomposition: DecompositionType::BitDecomposition(allocated_bits),
+        num_limbs: 1,
+        num_bits,
+        num_gates,
     })
 }
 
@@ -439,6 +463,9 @@ pub fn enforce_range_check_using_custom_gate<E: Engine, CS: ConstraintSystem<E>>
     Ok(RangeCheckDecomposition {
         chunks_bitlength: 2,
         decomposition: DecompositionType::ChunkDecomposition(result),
+        num_limbs: 1,
+        num_bits,
+        num_gates: num_gates + 1,
     })
 }
 
@@ -522,7 +549,8 @@ pub fn enforce_range_check_using_bitop_table<E: Engine, CS: ConstraintSystem<E>>
     {
         increment_short_checks_count();
     }
-    increment_total_gates_count((num_chunks + 1 + should_enforce_for_shifted_chunk as usize)/2);
+    let num_gates = (num_chunks + 1 + should_enforce_for_shifted_chunk as usize)/2;
+    increment_total_gates_count(num_gates);
     
     let value = var.get_value().map(|x| { fe_to_biguint(&x) });
     let chunks = split_some_into_fixed_number_of_limbs(value, chunk_width, num_chunks).into_iter().map(|x| {
@@ -575,6 +603,9 @@ pub fn enforce_range_check_using_bitop_table<E: Engine, CS: ConstraintSystem<E>>
     Ok(RangeCheckDecomposition {
         chunks_bitlength: chunk_width,
         decomposition: DecompositionType::ChunkDecomposition(chunks),
+        num_limbs: 1,
+        num_bits,
+        num_gates,
     })
 }
 
@@ -681,5 +712,34 @@ mod test {
 
         assert!(cs.is_satisfied()); 
     }
+
+    #[test]
+    fn test_range_check_decomposition_reported_cost() {
+        let mut cs = TrivialAssembly::<Bn256, Width4WithCustomGates, Width4MainGateWithDNext>::new();
+        inscribe_default_bitop_range_table(&mut cs).unwrap();
+        // the first range check may additionally allocate the explicit zero variable, so warm up
+        let var = AllocatedNum::alloc(&mut cs, || Ok(u64_to_fe::<Fr>(0b1111111))).unwrap();
+        constraint_bit_length(&mut cs, &var, 8).unwrap();
+
+        let mut decompositions = vec![];
+        for num_bits in [17usize, 68, 70, 80].iter() {
+            let value = u64_to_fe::<Fr>((1u64 << 16) + 1);
+            let var = AllocatedNum::alloc(&mut cs, || Ok(value)).unwrap();
+            let start = cs.get_current_step_number();
+            let decomposition = constraint_bit_length_ext(&mut cs, &var, *num_bits).unwrap();
+            let actual_gates = cs.get_current_step_number() - start;
+
+            assert_eq!(decomposition.get_num_limbs(), 1);
+            assert_eq!(decomposition.get_num_bits(), *num_bits);
+            assert_eq!(decomposition.get_num_gates(), actual_gates);
+            decompositions.push(decomposition);
+        }
+
+        let total = RangeCheckDecomposition::combine(&decompositions);
+        assert_eq!(total.get_num_limbs(), decompositions.len());
+        assert_eq!(total.get_num_bits(), 17 + 68 + 70 + 80);
+        assert_eq!(total.get_num_gates(), decompositions.iter().map(|x| x.get_num_gates()).sum());
+        assert!(cs.is_satisfied());
+    }
 }
 }
\ No newline at end of file
@@ -0,0 +1,79 @@
+// serializable witness part of the field element: only the value is stored, not the circuit structure
+// (limbs, variables and reduction status are recreated on allocation). Allows to dump the witness of a
+// failing proof and to replay it later
+use super::*;
+use num_bigint::BigUint;
+use serde::{Serialize, Deserialize};
+
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FieldElementWitness {
+    pub value: Option<BigUint>
+}
+
+impl FieldElementWitness {
+    pub fn from_value<F: PrimeField>(value: Option<F>) -> Self {
+        FieldElementWitness { value: value.map(|x| fe_to_biguint(&x)) }
+    }
+
+    // deserialized data is untrusted: values which are not reduced modulo F::char are rejected
+    pub fn to_value<F: PrimeField>(&self) -> Result<Option<F>, SynthesisError> {
+        match self.value {
+            Some(ref v) => {
+                if *v >= repr_to_biguint::<F>(&F::char()) {
+                    return Err(SynthesisError::Unsatisfiable);
+                }
+                Ok(Some(biguint_to_fe::<F>(v.clone())))
+            },
+            None => Ok(None)
+        }
+    }
+}
+
+impl<'a, E: Engine, F: PrimeField> FieldElement<'a, E, F> {
+    pub fn get_witness(&self) -> FieldElementWitness {
+        FieldElementWitness::from_value(self.get_field_value())
+    }
+
+    #[track_caller]
+    pub fn alloc_from_witness<CS: ConstraintSystem<E>>(
+        cs: &mut CS, witness: &FieldElementWitness, params: &'a RnsParameters<E, F>
+    ) -> Result<Self, SynthesisError> {
+        let value = witness.to_value::<F>()?;
+        Self::alloc(cs, value, params)
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::bellman::pairing::bn256::{Fq, Bn256};
+    use crate::plonk::circuit::Width4WithCustomGates;
+    use crate::bellman::plonk::better_better_cs::gates::selector_optimized_with_d_next::SelectorOptimizedWidth4MainGateWithDNext;
+    use rand::Rng;
+
+    #[test]
+    fn test_field_element_witness_round_trip() {
+        let mut cs = TrivialAssembly::<Bn256, Width4WithCustomGates, SelectorOptimizedWidth4MainGateWithDNext>::new();
+        inscribe_default_bitop_range_table(&mut cs).unwrap();
+        let params = RnsParameters::<Bn256, Fq>::new_optimal(&mut cs, 80usize);
+        let mut rng = rand::thread_rng();
+
+        let a_f: Fq = rng.gen();
+        let a = FieldElement::alloc(&mut cs, Some(a_f), &params).unwrap();
+        let encoded = serde_json::to_string(&a.get_witness()).unwrap();
+        let decoded: FieldElementWitness = serde_json::from_str(&encoded).unwrap();
+        let b = FieldElement::alloc_from_witness(&mut cs, &decoded, &params).unwrap();
+        assert_eq!(b.get_field_value(), Some(a_f));
+
+        let unknown = FieldElementWitness::from_value::<Fq>(None);
+        let encoded = serde_json::to_string(&unknown).unwrap();
+        let decoded: FieldElementWitness = serde_json::from_str(&encoded).unwrap();
+        assert_eq!(decoded.to_value::<Fq>().unwrap(), None);
+
+        let unreduced = FieldElementWitness { value: Some(repr_to_biguint::<Fq>(&Fq::char())) };
+        assert!(unreduced.to_value::<Fq>().is_err());
+        assert!(cs.is_satisfied());
+    }
+}
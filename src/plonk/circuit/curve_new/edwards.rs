@@ -0,0 +1,304 @@
+use crate::bellman::pairing::Engine;
+use crate::bellman::pairing::ff::{Field, PrimeField};
+use crate::bellman::SynthesisError;
+use crate::bellman::plonk::better_better_cs::cs::ConstraintSystem;
+
+use super::super::boolean::Boolean;
+use crate::plonk::circuit::bigint_new::*;
+
+
+// twisted Edwards curve a * x^2 + y^2 = 1 + d * x^2 * y^2 over the (non-native) base field:
+// if a is a square and d is not, the addition law below is complete, i.e. there are no exceptional cases at all
+// (doubling, addition of the opposite points and of the neutral element (0, 1) are handled by the same formula)
+pub trait TwistedEdwardsCurve: Clone {
+    type Base: PrimeField;
+    type Scalar: PrimeField;
+
+    fn a_coeff() -> Self::Base;
+    fn d_coeff() -> Self::Base;
+}
+
+// Jubjub: -x^2 + y^2 = 1 + d * x^2 * y^2 with d = -(10240/10241) over the scalar field of BLS12-381
+#[derive(Clone, Debug)]
+pub struct JubjubCurve;
+
+impl TwistedEdwardsCurve for JubjubCurve {
+    type Base = crate::bellman::pairing::bls12_381::Fr;
+    type Scalar = crate::jubjub::fs::Fs;
+
+    fn a_coeff() -> Self::Base {
+        let mut a = Self::Base::one();
+        a.negate();
+        a
+    }
+
+    fn d_coeff() -> Self::Base {
+        Self::Base::from_str("19257038036680949359750312669786877991949435402254120286184196891950884077233").unwrap()
+    }
+}
+
+
+#[derive(Clone, Debug)]
+pub struct EdwardsPoint<'a, E: Engine, C: TwistedEdwardsCurve> {
+    pub x: FieldElement<'a, E, C::Base>,
+    pub y: FieldElement<'a, E, C::Base>,
+    pub value: Option<(C::Base, C::Base)>,
+}
+
+impl<'a, E: Engine, C: TwistedEdwardsCurve> EdwardsPoint<'a, E, C> {
+    pub fn get_value(&self) -> Option<(C::Base, C::Base)> {
+        self.value
+    }
+
+    pub fn is_constant(&self) -> bool {
+        self.x.is_constant() & self.y.is_constant()
+    }
+
+    // neutral element of the group
+    pub fn zero(params: &'a RnsParameters<E, C::Base>) -> Self {
+        Self::constant((C::Base::zero(), C::Base::one()), params)
+    }
+
+    pub fn constant(value: (C::Base, C::Base), params: &'a RnsParameters<E, C::Base>) -> Self {
+        assert!(Self::value_is_on_curve(value), "point is not on the curve");
+        let x = FieldElement::constant(value.0, params);
+        let y = FieldElement::constant(value.1, params);
+
+        Self { x, y, value: Some(value) }
+    }
+
+    #[track_caller]
+    pub fn alloc<CS: ConstraintSystem<E>>(
+        cs: &mut CS, value: Option<(C::Base, C::Base)>, params: &'a RnsParameters<E, C::Base>
+    ) -> Result<Self, SynthesisError> {
+        let new = Self::alloc_unchecked(cs, value, params)?;
+        new.enforce_on_curve(cs)?;
+        Ok(new)
+    }
+
+    pub fn alloc_unchecked<CS: ConstraintSystem<E>>(
+        cs: &mut CS, value: Option<(C::Base, C::Base)>, params: &'a RnsParameters<E, C::Base>
+    ) -> Result<Self, SynthesisError> {
+        let x = FieldElement::alloc(cs, value.map(|v| v.0), params)?;
+        let y = FieldElement::alloc(cs, value.map(|v| v.1), params)?;
+
+        Ok(Self { x, y, value })
+    }
+
+    // a * x^2 + y^2 = 1 + d * x^2 * y^2
+    pub fn enforce_on_curve<CS: ConstraintSystem<E>>(&self, cs: &mut CS) -> Result<(), SynthesisError> {
+        let params = self.x.representation_params;
+        let a = FieldElement::constant(C::a_coeff(), params);
+        let d = FieldElement::constant(C::d_coeff(), params);
+        let x_squared = self.x.square(cs)?;
+        let y_squared = self.y.square(cs)?;
+
+        let mut chain = FieldElementsChain::new();
+        chain.add_pos_term(&y_squared);
+        let mut lhs = FieldElement::mul_with_chain(cs, &a, &x_squared, chain)?;
+
+        let x_squared_y_squared = x_squared.mul(cs, &y_squared)?;
+        let mut chain = FieldElementsChain::new();
+        chain.add_pos_term(&FieldElement::one(params));
+        let mut rhs = FieldElement::mul_with_chain(cs, &d, &x_squared_y_squared, chain)?;
+
+        FieldElement::enforce_equal(cs, &mut lhs, &mut rhs)
+    }
+
+    pub fn negate<CS: ConstraintSystem<E>>(&self, cs: &mut CS) -> Result<Self, SynthesisError> {
+        let x = self.x.negate(cs)?;
+        let value = self.value.map(|(x, y)| {
+            let mut x = x;
+            x.negate();
+            (x, y)
+        });
+
+        Ok(Self { x, y: self.y.clone(), value })
+    }
+
+    pub fn conditionally_select<CS: ConstraintSystem<E>>(
+        cs: &mut CS, flag: &Boolean, first: &Self, second: &Self
+    ) -> Result<Self, SynthesisError> {
+        let x = FieldElement::conditionally_select(cs, flag, &first.x, &second.x)?;
+        let y = FieldElement::conditionally_select(cs, flag, &first.y, &second.y)?;
+        let value = match (flag.get_value(), first.value, second.value) {
+            (Some(true), Some(p), _) => Some(p),
+            (Some(false), _, Some(p)) => Some(p),
+            (_, _, _) => None
+        };
+
+        Ok(Self { x, y, value })
+    }
+
+    // x3 = (x1 * y2 + y1 * x2) / (1 + d * x1 * x2 * y1 * y2)
+    // y3 = (y1 * y2 - a * x1 * x2) / (1 - d * x1 * x2 * y1 * y2)
+    // both denominators are nonzero for all the points of the curve, as the law is complete
+    #[track_caller]
+    pub fn add<CS: ConstraintSystem<E>>(&self, cs: &mut CS, other: &Self) -> Result<Self, SynthesisError> {
+        let params = self.x.representation_params;
+        let a = FieldElement::constant(C::a_coeff(), params);
+        let d = FieldElement::constant(C::d_coeff(), params);
+        let one = FieldElement::one(params);
+
+        let x1_x2 = self.x.mul(cs, &other.x)?;
+        let y1_y2 = self.y.mul(cs, &other.y)?;
+        let x1_y2 = self.x.mul(cs, &other.y)?;
+        let y1_x2 = self.y.mul(cs, &other.x)?;
+        let t = x1_x2.mul(cs, &y1_y2)?;
+        let d_t = d.mul(cs, &t)?;
+        let x_den = one.add(cs, &d_t)?;
+        let y_den = one.sub(cs, &d_t)?;
+
+        let mut chain = FieldElementsChain::new();
+        chain.add_pos_term(&x1_y2).add_pos_term(&y1_x2);
+        let x = FieldElement::div_with_chain(cs, chain, &x_den)?;
+
+        let a_x1_x2 = a.mul(cs, &x1_x2)?;
+        let mut chain = FieldElementsChain::new();
+        chain.add_pos_term(&y1_y2).add_neg_term(&a_x1_x2);
+        let y = FieldElement::div_with_chain(cs, chain, &y_den)?;
+
+        let value = match (self.value, other.value) {
+            (Some(p), Some(q)) => Some(Self::add_values(p, q)),
+            _ => None
+        };
+
+        Ok(Self { x, y, value })
+    }
+
+    pub fn double<CS: ConstraintSystem<E>>(&self, cs: &mut CS) -> Result<Self, SynthesisError> {
+        self.add(cs, self)
+    }
+
+    // double-and-add starting from the most significant bit: no exceptional cases thanks to the complete law,
+    // so the same circuit works for any scalar (zero included) and any point of the curve
+    #[track_caller]
+    pub fn mul_by_scalar<CS: ConstraintSystem<E>>(
+        &self, cs: &mut CS, scalar: &mut FieldElement<'a, E, C::Scalar>
+    ) -> Result<Self, SynthesisError> {
+        let params = self.x.representation_params;
+        let bits = scalar.decompose_into_binary_representation(cs)?;
+
+        let mut acc = Self::zero(params);
+        for bit in bits.iter().rev() {
+            acc = acc.double(cs)?;
+            let added = acc.add(cs, self)?;
+            acc = Self::conditionally_select(cs, bit, &added, &acc)?;
+        }
+
+        Ok(acc)
+    }
+
+    fn value_is_on_curve(value: (C::Base, C::Base)) -> bool {
+        let (x, y) = value;
+        let mut x_squared = x;
+        x_squared.square();
+        let mut y_squared = y;
+        y_squared.square();
+
+        let mut lhs = x_squared;
+        lhs.mul_assign(&C::a_coeff());
+        lhs.add_assign(&y_squared);
+
+        let mut rhs = x_squared;
+        rhs.mul_assign(&y_squared);
+        rhs.mul_assign(&C::d_coeff());
+        rhs.add_assign(&C::Base::one());
+
+        lhs == rhs
+    }
+
+    fn add_values(p: (C::Base, C::Base), q: (C::Base, C::Base)) -> (C::Base, C::Base) {
+        let ((x1, y1), (x2, y2)) = (p, q);
+        let mut x1_x2 = x1;
+        x1_x2.mul_assign(&x2);
+        let mut y1_y2 = y1;
+        y1_y2.mul_assign(&y2);
+        let mut d_t = x1_x2;
+        d_t.mul_assign(&y1_y2);
+        d_t.mul_assign(&C::d_coeff());
+
+        let mut x_num = x1;
+        x_num.mul_assign(&y2);
+        let mut y1_x2 = y1;
+        y1_x2.mul_assign(&x2);
+        x_num.add_assign(&y1_x2);
+        let mut x_den = C::Base::one();
+        x_den.add_assign(&d_t);
+        x_num.mul_assign(&x_den.inverse().expect("addition law is complete"));
+
+        let mut y_num = y1_y2;
+        let mut a_x1_x2 = x1_x2;
+        a_x1_x2.mul_assign(&C::a_coeff());
+        y_num.sub_assign(&a_x1_x2);
+        let mut y_den = C::Base::one();
+        y_den.sub_assign(&d_t);
+        y_num.mul_assign(&y_den.inverse().expect("addition law is complete"));
+
+        (x_num, y_num)
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::bellman::pairing::bn256::Bn256;
+    use crate::bellman::pairing::bls12_381::{Bls12, Fr as JubjubBase};
+    use crate::jubjub::{JubjubBls12, Unknown};
+    use crate::jubjub::edwards::Point;
+    use crate::jubjub::fs::Fs;
+    use crate::plonk::circuit::boolean::AllocatedBit;
+    use plonk::circuit::Width4WithCustomGates;
+    use bellman::plonk::better_better_cs::gates::selector_optimized_with_d_next::SelectorOptimizedWidth4MainGateWithDNext;
+    use rand::{XorShiftRng, SeedableRng, Rng};
+    use bellman::plonk::better_better_cs::cs::*;
+
+    #[test]
+    fn test_arithmetic_for_jubjub() {
+        let mut cs = TrivialAssembly::<Bn256, Width4WithCustomGates, SelectorOptimizedWidth4MainGateWithDNext>::new();
+        inscribe_default_bitop_range_table(&mut cs).unwrap();
+        let params = RnsParameters::<Bn256, JubjubBase>::new_optimal(&mut cs, 80usize);
+        let scalar_params = RnsParameters::<Bn256, Fs>::new_optimal(&mut cs, 80usize);
+        let jubjub_params = JubjubBls12::new();
+        let mut rng = XorShiftRng::from_seed([0x5dbe6259, 0x8d313d76, 0x3237db17, 0xe5bc0654]);
+
+        let p_wit = Point::<Bls12, Unknown>::rand(&mut rng, &jubjub_params);
+        let q_wit = Point::<Bls12, Unknown>::rand(&mut rng, &jubjub_params);
+        let p = EdwardsPoint::<Bn256, JubjubCurve>::alloc(&mut cs, Some(p_wit.into_xy()), &params).unwrap();
+        let q = EdwardsPoint::<Bn256, JubjubCurve>::alloc(&mut cs, Some(q_wit.into_xy()), &params).unwrap();
+
+        let sum = p.add(&mut cs, &q).unwrap();
+        assert_eq!(sum.get_value(), Some(p_wit.add(&q_wit, &jubjub_params).into_xy()));
+        assert_eq!(sum.x.get_field_value(), sum.get_value().map(|v| v.0));
+        assert_eq!(sum.y.get_field_value(), sum.get_value().map(|v| v.1));
+
+        // the same formula takes care of doubling, P + (-P) and addition of the neutral element
+        let doubled = p.double(&mut cs).unwrap();
+        assert_eq!(doubled.get_value(), Some(p_wit.double(&jubjub_params).into_xy()));
+        let minus_p = p.negate(&mut cs).unwrap();
+        let zero = p.add(&mut cs, &minus_p).unwrap();
+        assert_eq!(zero.get_value(), Some((JubjubBase::zero(), JubjubBase::one())));
+        assert_eq!(zero.x.get_field_value(), Some(JubjubBase::zero()));
+        let same = p.add(&mut cs, &EdwardsPoint::zero(&params)).unwrap();
+        assert_eq!(same.get_value(), Some(p_wit.into_xy()));
+
+        let flag = Boolean::from(AllocatedBit::alloc(&mut cs, Some(false)).unwrap());
+        let selected = EdwardsPoint::conditionally_select(&mut cs, &flag, &p, &q).unwrap();
+        assert_eq!(selected.get_value(), Some(q_wit.into_xy()));
+
+        let scalar_wit: Fs = rng.gen();
+        let mut scalar = FieldElement::alloc(&mut cs, Some(scalar_wit), &scalar_params).unwrap();
+        let n = cs.get_current_step_number();
+        let product = p.mul_by_scalar(&mut cs, &mut scalar).unwrap();
+        println!("num of gates for scalar multiplication: {}", cs.get_current_step_number() - n);
+        assert_eq!(product.get_value(), Some(p_wit.mul(scalar_wit, &jubjub_params).into_xy()));
+        assert!(cs.is_satisfied());
+
+        // points off the curve are rejected
+        let (x, mut y) = p_wit.into_xy();
+        y.add_assign(&JubjubBase::one());
+        let _ = EdwardsPoint::<Bn256, JubjubCurve>::alloc(&mut cs, Some((x, y)), &params).unwrap();
+        assert!(!cs.is_satisfied());
+    }
+}
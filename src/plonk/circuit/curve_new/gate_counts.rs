@@ -0,0 +1,66 @@
+// regression bounds on the number of gates spent by the core curve operations over bn256 with 80-bit limbs.
+// The bounds are NOT measured counts: they are derived from the cost model of estimate_windowed_mul_cost
+// (multiplication of field elements with n = 4 binary limbs ~ (n+1)^2 gates) with 2x headroom for range checks
+// and reductions. The test prints the actual counts: once they are recorded, the bounds should be tightened
+// to the measured values plus a small margin, so that silent cost regressions are caught
+use super::*;
+use crate::bellman::pairing::bn256::{Fq, Bn256, Fr, G1Affine};
+use crate::bellman::pairing::{GenericCurveAffine, GenericCurveProjective};
+use crate::plonk::circuit::bigint_new::*;
+use crate::plonk::circuit::Width4WithCustomGates;
+use crate::bellman::plonk::better_better_cs::gates::selector_optimized_with_d_next::SelectorOptimizedWidth4MainGateWithDNext;
+use crate::bellman::plonk::better_better_cs::cs::*;
+use rand::Rng;
+
+// 3 multiplications (division included) + 2 normalizations for the x-inequality check
+const ADD_UNEQUAL_BOUND: usize = 1_000;
+// 4 multiplications (division included)
+const DOUBLE_BOUND: usize = 1_000;
+// 254 iterations of projective doubling, mixed addition and selection: ~25 multiplications and 3 selections each
+const PRIME_ORDER_MUL_BOUND: usize = 300_000;
+// 254 iterations of double-and-add with two divisions and a selection each
+const COMPOSITE_ORDER_MUL_BOUND: usize = 300_000;
+
+
+#[test]
+fn test_gate_counts_of_core_curve_operations() {
+    let mut cs = TrivialAssembly::<Bn256, Width4WithCustomGates, SelectorOptimizedWidth4MainGateWithDNext>::new();
+    inscribe_default_bitop_range_table(&mut cs).unwrap();
+    let params = RnsParameters::<Bn256, Fq>::new_optimal(&mut cs, 80usize);
+    let scalar_params = RnsParameters::<Bn256, Fr>::new_optimal(&mut cs, 80usize);
+    let mut rng = rand::thread_rng();
+
+    let a_wit: G1Affine = rng.gen();
+    let b_wit: G1Affine = rng.gen();
+    let scalar_wit: Fr = rng.gen();
+    let mut a = AffinePoint::alloc(&mut cs, Some(a_wit), &params).unwrap();
+    let mut b = AffinePoint::alloc(&mut cs, Some(b_wit), &params).unwrap();
+    let mut scalar = FieldElement::alloc(&mut cs, Some(scalar_wit), &scalar_params).unwrap();
+
+    let start = cs.get_current_step_number();
+    let _ = a.add_unequal(&mut cs, &mut b).unwrap();
+    let add_unequal_gates = cs.get_current_step_number() - start;
+
+    let start = cs.get_current_step_number();
+    let _ = a.double(&mut cs).unwrap();
+    let double_gates = cs.get_current_step_number() - start;
+
+    let start = cs.get_current_step_number();
+    let _ = a.mul_by_scalar_for_prime_order_curve(&mut cs, &mut scalar.clone()).unwrap();
+    let prime_order_mul_gates = cs.get_current_step_number() - start;
+
+    let start = cs.get_current_step_number();
+    let _ = a.mul_by_scalar_for_composite_order_curve(&mut cs, &mut scalar).unwrap();
+    let composite_order_mul_gates = cs.get_current_step_number() - start;
+
+    println!("add_unequal: {} gates", add_unequal_gates);
+    println!("double: {} gates", double_gates);
+    println!("mul_by_scalar_for_prime_order_curve: {} gates", prime_order_mul_gates);
+    println!("mul_by_scalar_for_composite_order_curve: {} gates", composite_order_mul_gates);
+
+    assert!(add_unequal_gates <= ADD_UNEQUAL_BOUND);
+    assert!(double_gates <= DOUBLE_BOUND);
+    assert!(prime_order_mul_gates <= PRIME_ORDER_MUL_BOUND);
+    assert!(composite_order_mul_gates <= COMPOSITE_ORDER_MUL_BOUND);
+    assert!(cs.is_satisfied());
+}
@@ -4,5 +4,27 @@ pub use self::sw_affine::*;
 pub mod sw_projective;
 pub use self::sw_projective::*;
 
+pub mod params;
+pub use self::params::*;
+
+#[macro_use]
+mod sw_curve_impl;
+
 pub mod secp256k1;
-pub use self::secp256k1::*;
\ No newline at end of file
+pub use self::secp256k1::*;
+
+pub mod edwards;
+pub use self::edwards::*;
+
+#[cfg(feature = "serde-witness")]
+pub mod witness;
+#[cfg(feature = "serde-witness")]
+pub use self::witness::*;
+
+pub mod secp256r1;
+
+pub mod pallas;
+pub mod vesta;
+
+#[cfg(test)]
+mod gate_counts;
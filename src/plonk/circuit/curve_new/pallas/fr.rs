@@ -0,0 +1,8 @@
+use crate::bellman::pairing::ff::*;
+
+
+// scalar field, R = 0x40000000000000000000000000000000224698fc0994a8dd8c46eb2100000001
+#[derive(PrimeField)]
+#[PrimeFieldModulus = "28948022309329048855892746252171976963363056481941647379679742748393362948097"]
+#[PrimeFieldGenerator = "5"]
+pub struct Fr(FrRepr);
\ No newline at end of file
@@ -0,0 +1,214 @@
+use crate::bellman::pairing::{
+    Engine,
+    GenericCurveAffine,
+};
+
+use crate::bellman::pairing::ff::PrimeField;
+
+use crate::bellman::{
+    SynthesisError,
+};
+
+use crate::bellman::plonk::better_better_cs::cs::ConstraintSystem;
+use crate::plonk::circuit::bigint_new::*;
+
+
+// returns true if A::char > B::char
+pub(crate) fn field_modulus_is_larger<A: PrimeField, B: PrimeField>() -> bool {
+    repr_to_biguint::<A>(&A::char()) > repr_to_biguint::<B>(&B::char())
+}
+
+// cross-field gadgets (reducing elements of the base field modulo the order of the scalar field)
+// assume that the base field is the larger one and should go through this check
+pub(crate) fn check_modulus_is_larger<A: PrimeField, B: PrimeField>() -> Result<(), SynthesisError> {
+    if field_modulus_is_larger::<A, B>() { Ok(()) } else { Err(SynthesisError::Unsatisfiable) }
+}
+
+
+// RNS parameters for both fields of the curve: coordinates live in the base field,
+// while the scalars by which we multiply live in the scalar field
+#[derive(Clone, Debug)]
+pub struct CurveCircuitParameters<E: Engine, G: GenericCurveAffine> where <G as GenericCurveAffine>::Base: PrimeField {
+    pub base_field_rns_params: RnsParameters<E, G::Base>,
+    pub scalar_field_rns_params: RnsParameters<E, G::Scalar>,
+    pub is_prime_order_curve: bool,
+    // domain separation tag from which the offset generator of the composite-order ladder is derived
+    pub offset_generator_dst: Vec<u8>,
+    _marker: std::marker::PhantomData<G>
+}
+
+impl<E: Engine, G: GenericCurveAffine> CurveCircuitParameters<E, G> where <G as GenericCurveAffine>::Base: PrimeField {
+    pub fn new(
+        base_field_rns_params: RnsParameters<E, G::Base>,
+        scalar_field_rns_params: RnsParameters<E, G::Scalar>,
+        is_prime_order_curve: bool
+    ) -> Self {
+        CurveCircuitParameters {
+            base_field_rns_params,
+            scalar_field_rns_params,
+            is_prime_order_curve,
+            offset_generator_dst: crate::constants::MULTIEXP_DST.to_vec(),
+            _marker: std::marker::PhantomData::<G>
+        }
+    }
+
+    pub fn set_offset_generator_dst(&mut self, dst: &[u8]) {
+        self.offset_generator_dst = dst.to_vec();
+    }
+
+    pub fn base_larger_than_scalar(&self) -> bool {
+        field_modulus_is_larger::<G::Base, G::Scalar>()
+    }
+
+    // computes x (mod q) for x in the base field: e.g. the r component of ECDSA signature
+    // returns Unsatisfiable error for the curves where the scalar field is larger than the base field
+    #[track_caller]
+    pub fn reduce_base_field_element_into_scalar_field<'a, CS: ConstraintSystem<E>>(
+        &'a self, cs: &mut CS, x: &mut FieldElement<'a, E, G::Base>
+    ) -> Result<FieldElement<'a, E, G::Scalar>, SynthesisError> {
+        check_modulus_is_larger::<G::Base, G::Scalar>()?;
+        x.reinterpret_in_field(cs, &self.scalar_field_rns_params)
+    }
+
+    // is_prime_order_curve (u8) || base field RNS parameters || scalar field RNS parameters ||
+    // length of offset generator DST (u16, big-endian) || DST, s.t. the parameters may be computed once 
+    // and reused across circuits
+    pub fn to_bytes(&self) -> Vec<u8> {
+        assert!(self.offset_generator_dst.len() <= u16::MAX as usize, "offset generator DST is too long");
+        let mut bytes = vec![self.is_prime_order_curve as u8];
+        self.base_field_rns_params.write(&mut bytes).expect("must write into vector");
+        self.scalar_field_rns_params.write(&mut bytes).expect("must write into vector");
+        bytes.extend_from_slice(&(self.offset_generator_dst.len() as u16).to_be_bytes());
+        bytes.extend_from_slice(&self.offset_generator_dst[..]);
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> std::io::Result<Self> {
+        use std::io::{Read, Error, ErrorKind};
+        let mut reader = bytes;
+        let mut flag = [0u8; 1];
+        reader.read_exact(&mut flag)?;
+        let is_prime_order_curve = match flag[0] {
+            0 => false,
+            1 => true,
+            _ => return Err(Error::new(ErrorKind::InvalidData, "invalid is_prime_order_curve flag")),
+        };
+        let base_field_rns_params = RnsParameters::<E, G::Base>::read(&mut reader)?;
+        let scalar_field_rns_params = RnsParameters::<E, G::Scalar>::read(&mut reader)?;
+        let mut dst_len = [0u8; 2];
+        reader.read_exact(&mut dst_len)?;
+        let mut dst = vec![0u8; u16::from_be_bytes(dst_len) as usize];
+        reader.read_exact(&mut dst)?;
+        if !reader.is_empty() {
+            return Err(Error::new(ErrorKind::InvalidData, "trailing bytes after curve parameters"));
+        }
+
+        let mut params = Self::new(base_field_rns_params, scalar_field_rns_params, is_prime_order_curve);
+        params.set_offset_generator_dst(&dst);
+        Ok(params)
+    }
+}
+
+impl<E: Engine, G: GenericCurveAffine + rand::Rand> CurveCircuitParameters<E, G> where <G as GenericCurveAffine>::Base: PrimeField {
+    // the same as AffinePoint::offset_generator_from_dst for the DST of these parameters
+    pub fn offset_generator(&self) -> G {
+        crate::constants::make_random_points_with_unknown_discrete_log::<G>(&self.offset_generator_dst[..], 1)[0]
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::bellman::pairing::bn256::{Fq, Bn256, Fr, G1Affine};
+    use plonk::circuit::Width4WithCustomGates;
+    use bellman::plonk::better_better_cs::gates::{selector_optimized_with_d_next::SelectorOptimizedWidth4MainGateWithDNext, self};
+    use rand::{XorShiftRng, SeedableRng, Rng};
+    use bellman::plonk::better_better_cs::cs::*;
+
+    #[test]
+    fn test_base_to_scalar_reduction_for_bn256() {
+        let mut cs = TrivialAssembly::<Bn256, Width4WithCustomGates, SelectorOptimizedWidth4MainGateWithDNext>::new();
+        inscribe_default_bitop_range_table(&mut cs).unwrap();
+        let base_params = RnsParameters::<Bn256, Fq>::new_optimal(&mut cs, 80usize);
+        let scalar_params = RnsParameters::<Bn256, Fr>::new_optimal(&mut cs, 80usize);
+        let params = CurveCircuitParameters::<Bn256, G1Affine>::new(base_params, scalar_params, true);
+        assert!(params.base_larger_than_scalar());
+
+        let mut rng = rand::thread_rng();
+        let x_f: Fq = rng.gen();
+        let expected = fe_to_biguint(&x_f) % repr_to_biguint::<Fr>(&Fr::char());
+        let expected = biguint_to_fe::<Fr>(expected);
+
+        let mut x = FieldElement::alloc(&mut cs, Some(x_f), &params.base_field_rns_params).unwrap();
+        let res = params.reduce_base_field_element_into_scalar_field(&mut cs, &mut x).unwrap();
+        assert_eq!(res.get_field_value().unwrap(), expected);
+        assert!(cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_curve_params_serialization_round_trip() {
+        use crate::plonk::circuit::curve_new::AffinePoint;
+        use crate::plonk::circuit::curve_new::secp256k1::PointAffine as SecpG1;
+        use crate::plonk::circuit::curve_new::secp256k1::fq::Fq as SecpFq;
+
+        let mut cs = TrivialAssembly::<Bn256, Width4WithCustomGates, SelectorOptimizedWidth4MainGateWithDNext>::new();
+        inscribe_default_bitop_range_table(&mut cs).unwrap();
+        let base_params = RnsParameters::<Bn256, Fq>::new_optimal(&mut cs, 80usize);
+        let scalar_params = RnsParameters::<Bn256, Fr>::new_optimal(&mut cs, 80usize);
+        let mut params = CurveCircuitParameters::<Bn256, G1Affine>::new(base_params, scalar_params, true);
+        params.base_field_rns_params.set_auto_reduce(true);
+
+        let bytes = params.to_bytes();
+        let restored = CurveCircuitParameters::<Bn256, G1Affine>::from_bytes(&bytes).unwrap();
+        assert_eq!(restored.base_field_rns_params, params.base_field_rns_params);
+        assert_eq!(restored.scalar_field_rns_params, params.scalar_field_rns_params);
+        assert_eq!(restored.is_prime_order_curve, params.is_prime_order_curve);
+        assert_eq!(restored.offset_generator_dst, params.offset_generator_dst);
+        assert_eq!(restored.to_bytes(), bytes);
+
+        // both sets of parameters lead to the same circuit
+        let mut rng = rand::thread_rng();
+        let a_wit: G1Affine = rng.gen();
+        let b_wit: G1Affine = rng.gen();
+        let mut fingerprints = vec![];
+        for p in vec![&params, &restored].into_iter() {
+            let mut circuit = TrivialAssembly::<Bn256, Width4WithCustomGates, SelectorOptimizedWidth4MainGateWithDNext>::new();
+            inscribe_default_bitop_range_table(&mut circuit).unwrap();
+            let mut a = AffinePoint::alloc(&mut circuit, Some(a_wit), &p.base_field_rns_params).unwrap();
+            let mut b = AffinePoint::alloc(&mut circuit, Some(b_wit), &p.base_field_rns_params).unwrap();
+            let _ = a.add_unequal(&mut circuit, &mut b).unwrap();
+            assert!(circuit.is_satisfied());
+            fingerprints.push(circuit.get_current_step_number());
+        }
+        assert_eq!(fingerprints[0], fingerprints[1]);
+
+        // the layout is validated against the curve: bn256 parameters can not be loaded for secp256k1
+        assert!(CurveCircuitParameters::<Bn256, SecpG1>::from_bytes(&bytes).is_err());
+        assert!(CurveCircuitParameters::<Bn256, G1Affine>::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+
+        // base field parameters of secp256k1 in place of the bn256 ones
+        let secp_base_params = RnsParameters::<Bn256, SecpFq>::new_optimal(&mut cs, 64usize);
+        let mut tampered = vec![params.is_prime_order_curve as u8];
+        secp_base_params.write(&mut tampered).unwrap();
+        params.scalar_field_rns_params.write(&mut tampered).unwrap();
+        tampered.extend_from_slice(&(params.offset_generator_dst.len() as u16).to_be_bytes());
+        tampered.extend_from_slice(&params.offset_generator_dst[..]);
+        assert!(CurveCircuitParameters::<Bn256, G1Affine>::from_bytes(&tampered).is_err());
+
+        // length of the offset generator DST points past the end of the encoding
+        let mut tampered = bytes[..bytes.len() - params.offset_generator_dst.len() - 2].to_vec();
+        tampered.extend_from_slice(&(params.offset_generator_dst.len() as u16 + 1).to_be_bytes());
+        tampered.extend_from_slice(&params.offset_generator_dst[..]);
+        assert!(CurveCircuitParameters::<Bn256, G1Affine>::from_bytes(&tampered).is_err());
+    }
+
+    #[test]
+    fn test_modulus_relation_for_inverse_case() {
+        // pretend that Fr is the base field and Fq is the scalar one: the reduction is not applicable
+        assert!(field_modulus_is_larger::<Fq, Fr>());
+        assert!(!field_modulus_is_larger::<Fr, Fq>());
+        assert!(check_modulus_is_larger::<Fq, Fr>().is_ok());
+        assert!(check_modulus_is_larger::<Fr, Fq>().is_err());
+    }
+}
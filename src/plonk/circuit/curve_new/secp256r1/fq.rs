@@ -0,0 +1,8 @@
+use crate::bellman::pairing::ff::*;
+
+
+// base field, Q = 0xFFFFFFFF00000001000000000000000000000000FFFFFFFFFFFFFFFFFFFFFFFF
+#[derive(PrimeField)]
+#[PrimeFieldModulus = "115792089210356248762697446949407573530086143415290314195533631308867097853951"]
+#[PrimeFieldGenerator = "6"]
+pub struct Fq(FqRepr);
\ No newline at end of file
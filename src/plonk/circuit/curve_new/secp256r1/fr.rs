@@ -0,0 +1,8 @@
+use crate::bellman::pairing::ff::*;
+
+
+// scalar field, R = 0xFFFFFFFF00000000FFFFFFFFFFFFFFFFBCE6FAADA7179E84F3B9CAC2FC632551
+#[derive(PrimeField)]
+#[PrimeFieldModulus = "115792089210356248762697446949407573529996955224135760342422259061068512044369"]
+#[PrimeFieldGenerator = "7"]
+pub struct Fr(FrRepr);
\ No newline at end of file
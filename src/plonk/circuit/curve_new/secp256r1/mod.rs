@@ -0,0 +1,33 @@
+use crate::bellman::pairing::ff::BitIterator;
+use crate::bellman::pairing::{GenericCurveAffine, GenericCurveProjective, GroupDecodingError, EncodingBytes, GenericUncompressedEncodable, GenericCompressedEncodable};
+use crate::bellman::pairing::ff::*;
+use rand::*;
+
+
+pub mod fr;
+pub mod fq;
+
+use self::fr::*;
+use self::fq::*;
+
+short_weierstrass_curve_impl!("Secp256r1", minus_three_a);
+
+impl PointAffine {
+    fn get_coeff_a() -> <Self as GenericCurveAffine>::Base {
+        let mut a = Fq::from_str("3").unwrap();
+        a.negate();
+        a
+    }
+
+    fn get_coeff_b() -> <Self as GenericCurveAffine>::Base {
+        crate::bellman::pairing::ff::from_hex::<Fq>("0x5AC635D8AA3A93E7B3EBBD55769886BC651D06B0CC53B0F63BCE3C3E27D2604B").unwrap()
+    }
+
+    fn get_generator() -> Self {
+        Self {
+            x: crate::bellman::pairing::ff::from_hex::<Fq>("0x6B17D1F2E12C4247F8BCE6E563A440F277037D812DEB33A0F4A13945D898C296").unwrap(),
+            y: crate::bellman::pairing::ff::from_hex::<Fq>("0x4FE342E2FE1A7F9B8EE7EB4A7C0F9E162BCE33576B315ECECBB6406837BF51F5").unwrap(),
+            infinity: false
+        }
+    }
+}
@@ -48,6 +48,40 @@ use num_integer::Integer;
 
 use crate::plonk::circuit::bigint_new::*;
 use crate::plonk::circuit::curve_new::sw_projective::*;
+use crate::plonk::circuit::curve_new::params::CurveCircuitParameters;
+
+// windows larger than that lead to the tables of unreasonable size
+const MAX_WINDOW_SIZE: usize = 8;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PointByScalarMulStrategy {
+    // bit-by-bit double-and-add
+    Basic,
+    // the table [P, 2P, ..., (2^w - 1)P] is precomputed once and the scalar is consumed by windows of w bits
+    FixedWindow(usize),
+    // the pair (R0, R1) with R1 - R0 = P is updated by a single addition and doubling per bit in affine form
+    MontgomeryLadder,
+}
+
+
+// curves with an efficiently computable endomorphism (x, y) -> (beta * x, -y) (here beta is a nontrivial
+// cube root of unity in the base field): on the prime order subgroup it acts as multiplication by lambda
+pub trait CurveWithEndomorphism: GenericCurveAffine {
+    fn endomorphism_beta() -> Self::Base;
+    fn endomorphism_lambda() -> Self::Scalar;
+}
+
+// curves of composite order h * r: points allocated from untrusted data are on the curve, but not necessarily
+// in the prime order subgroup, and multiplication by the cofactor h maps them there
+pub trait CurveWithCofactor: GenericCurveAffine {
+    fn cofactor() -> BigUint;
+}
+
+impl CurveWithCofactor for crate::bellman::pairing::bls12_381::G1Affine {
+    fn cofactor() -> BigUint {
+        BigUint::parse_bytes(b"396c8c005555e1568c00aaab0000aaab", 16).unwrap()
+    }
+}
 
 
 #[derive(Clone, Debug)]
@@ -58,10 +92,42 @@ pub struct AffinePoint<'a, E: Engine, G: GenericCurveAffine> where <G as Generic
     // all our points are regular (i.e. not points at infinity)
     // for this purpose we introduce lazy_select
     // if current point is actually a point at infinity than x, y may contain any values and are actually meaningless
-    //pub is_infinity: Boolean,
+    pub is_infinity: Boolean,
     pub value: Option<G>,
 }
 
+// non-adjacent form of m, least significant digit first: every digit is one of -1, 0, 1
+// and no two consecutive digits are nonzero
+fn compute_naf(m: &BigUint) -> Vec<i8> {
+    use num_traits::Zero;
+    let mut m = m.clone();
+    let four = BigUint::from(4u64);
+    let mut digits = vec![];
+    while !m.is_zero() {
+        if m.is_odd() {
+            // the digit is 2 - (m mod 4), so that m - digit is divisible by 4
+            let residue = (&m % &four).to_u32_digits()[0];
+            if residue == 1 {
+                digits.push(1);
+                m -= BigUint::from(1u64);
+            } else {
+                digits.push(-1);
+                m += BigUint::from(1u64);
+            }
+        } else {
+            digits.push(0);
+        }
+        m >>= 1;
+    }
+
+    digits
+}
+
+
+// the results of complete arithmetic may be the point at infinity: the name emphasizes
+// that the is_infinity flag of such points is meaningful and should be respected by the caller
+pub type AffinePointWithInfinity<'a, E, G> = AffinePoint<'a, E, G>;
+
 impl<'a, E: Engine, G: GenericCurveAffine> AffinePoint<'a, E, G> where <G as GenericCurveAffine>::Base: PrimeField {
     pub fn get_x(&self) -> FieldElement<'a, E, G::Base> {
         self.x.clone()
@@ -96,11 +162,32 @@ impl<'a, E: Engine, G: GenericCurveAffine> AffinePoint<'a, E, G> where <G as Gen
 
         let (x, x_decomposition) = FieldElement::alloc_ext(cs, x, params)?;
         let (y, y_decomposition) = FieldElement::alloc_ext(cs, y, params)?;
-        let new = Self { x, y, value};
+        let new = Self { x, y, is_infinity: Boolean::constant(false), value };
 
         Ok((new, x_decomposition, y_decomposition))
     }
 
+    // test support: allocates reproducible pseudo-random point [k] * G, where k is derived from H(seed),
+    // so that failing fuzzing cases may be replayed
+    #[cfg(test)]
+    pub fn deterministic_from_seed<CS: ConstraintSystem<E>>(
+        cs: &mut CS, seed: u64, params: &'a RnsParameters<E, G::Base>
+    ) -> Result<Self, SynthesisError> {
+        use rand::{Rng, SeedableRng};
+        use rand::chacha::ChaChaRng;
+
+        let h = blake2s_simd::blake2s(&seed.to_le_bytes());
+        let mut rng_seed = [0u32; 8];
+        for (i, chunk) in h.as_bytes().chunks_exact(4).enumerate() {
+            rng_seed[i] = u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+        }
+        let mut rng = ChaChaRng::from_seed(&rng_seed);
+        let scalar: G::Scalar = rng.gen();
+        let value = G::one().mul(scalar.into_repr()).into_affine();
+        
+        Self::alloc(cs, Some(value), params)
+    }
+
     pub unsafe fn from_xy_unchecked(
         x: FieldElement<'a, E, G::Base>,
         y: FieldElement<'a, E, G::Base>,
@@ -114,7 +201,7 @@ impl<'a, E: Engine, G: GenericCurveAffine> AffinePoint<'a, E, G> where <G as Gen
             }
         };
 
-        let new = Self {x, y, value };
+        let new = Self { x, y, is_infinity: Boolean::constant(false), value };
         new
     }
 
@@ -123,11 +210,42 @@ impl<'a, E: Engine, G: GenericCurveAffine> AffinePoint<'a, E, G> where <G as Gen
         let (x, y) = value.into_xy_unchecked();
         let x = FieldElement::constant(x, params);
         let y = FieldElement::constant(y, params);
-        let new = Self { x, y, value: Some(value) };
+        let new = Self { x, y, is_infinity: Boolean::constant(false), value: Some(value) };
 
         new
     }
 
+    // out-of-circuit reference for the results of scalar multiplication gadgets: all of them compute
+    // the plain [scalar] * base (the skew and the offset generators are internal to the ladders)
+    pub fn expected_scalar_mul(base: G, scalar: G::Scalar) -> G {
+        let mut tmp = base.into_projective();
+        tmp.mul_assign(scalar);
+        tmp.into_affine()
+    }
+
+    // point at infinity: coordinates are meaningless placeholders, only the flag matters
+    pub fn zero(params: &'a RnsParameters<E, G::Base>) -> Self {
+        let x = FieldElement::zero(params);
+        let y = FieldElement::zero(params);
+        Self { x, y, is_infinity: Boolean::constant(true), value: Some(G::zero()) }
+    }
+
+    // the same point at infinity under the name used with AffinePointWithInfinity, 
+    // e.g. AffinePointWithInfinity::identity(params) as the starting value of an accumulator
+    pub fn identity(params: &'a RnsParameters<E, G::Base>) -> AffinePointWithInfinity<'a, E, G> {
+        Self::zero(params)
+    }
+
+    // the flag is maintained by all the arithmetic, so no additional constraints are required;
+    // for points that are statically known to be finite this is a constant false
+    pub fn is_zero<CS: ConstraintSystem<E>>(&self, _cs: &mut CS) -> Result<Boolean, SynthesisError> {
+        Ok(self.is_infinity.clone())
+    }
+
+    // limbs of x followed by limbs of y, each coordinate takes exactly params.num_binary_limbs limbs 
+    // (least significant first): this order is stable and is what from_raw_limbs_representation expects.
+    // The representation is canonical only for normalized coordinates, so use finalize_for_export 
+    // before absorbing the point into transcript
     pub fn get_raw_limbs_representation<CS>(&self, cs: &mut CS) -> Result<Vec<Num<E>>, SynthesisError> 
     where CS: ConstraintSystem<E> {
         let mut res = self.x.get_raw_limbs_representation(cs)?;
@@ -136,6 +254,33 @@ impl<'a, E: Engine, G: GenericCurveAffine> AffinePoint<'a, E, G> where <G as Gen
         Ok(res)
     }
     
+    // deterministic total order on regular points (e.g. to prove that RAM access log is sorted): the points are
+    // compared as pairs (x, y) of canonical integers, i.e. the limb vectors of get_raw_limbs_representation
+    // are compared starting from the most significant limb of x
+    pub fn lexicographic_less_than<CS>(cs: &mut CS, a: &mut Self, b: &mut Self) -> Result<Boolean, SynthesisError> 
+    where CS: ConstraintSystem<E> {
+        let x_is_less = FieldElement::is_less_than(cs, &mut a.x, &mut b.x)?;
+        let x_is_equal = FieldElement::equals(cs, &mut a.x, &mut b.x)?;
+        let y_is_less = FieldElement::is_less_than(cs, &mut a.y, &mut b.y)?;
+        let tail = Boolean::and(cs, &x_is_equal, &y_is_less)?;
+        Boolean::or(cs, &x_is_less, &tail)
+    }
+
+    // NB: we don't go through Boolean::enforce_equal, which panics on unequal witnesses
+    pub fn enforce_lexicographic_less_than<CS>(cs: &mut CS, a: &mut Self, b: &mut Self) -> Result<(), SynthesisError> 
+    where CS: ConstraintSystem<E> {
+        match Self::lexicographic_less_than(cs, a, b)? {
+            Boolean::Constant(flag) => if flag { Ok(()) } else { Err(SynthesisError::Unsatisfiable) },
+            ref flag => {
+                let mut minus_one = E::Fr::one();
+                minus_one.negate();
+                let mut lc = flag.lc(E::Fr::one());
+                lc.add_assign_constant(minus_one);
+                lc.enforce_zero(cs)
+            }
+        }
+    }
+
     pub fn is_constant(&self) -> bool {
         self.x.is_constant() & self.y.is_constant()
     }
@@ -144,33 +289,210 @@ impl<'a, E: Engine, G: GenericCurveAffine> AffinePoint<'a, E, G> where <G as Gen
         self.value
     }
 
+    // invariant of all the arithmetic: the tracked value of the point is the one encoded by the witness
+    // of its coordinates (and flag). It is checked in debug builds at the end of every operation, 
+    // so that an error in the value tracking is caught where it occurs rather than in the final comparison
+    #[track_caller]
+    fn debug_check_value_consistency(&self) {
+        if !cfg!(debug_assertions) {
+            return;
+        }
+        let witness = (self.value, self.x.get_field_value(), self.y.get_field_value(), self.is_infinity.get_value());
+        if let (Some(value), Some(x), Some(y), Some(is_infinity)) = witness {
+            debug_assert_eq!(value.is_zero(), is_infinity, "value {} is inconsistent with the infinity flag", value);
+            if !is_infinity {
+                debug_assert!(value.into_xy_unchecked() == (x, y), "value {} is inconsistent with the coordinates", value);
+            }
+        }
+    }
+
     pub fn normalize_coordinates<CS: ConstraintSystem<E>>(&mut self, cs: &mut CS) -> Result<(), SynthesisError> {
         self.x.normalize(cs)?;
         self.y.normalize(cs)
     }
 
+    // arithmetic methods leave coordinates in whatever reduction status the computation left them,
+    // unless auto_reduce is set in the parameters - then the outputs are normalized
+    fn apply_output_reduction_policy<CS: ConstraintSystem<E>>(&mut self, cs: &mut CS) -> Result<(), SynthesisError> {
+        if self.x.representation_params.auto_reduce() {
+            self.normalize_coordinates(cs)?;
+        }
+        Ok(())
+    }
+
     pub fn enforce_if_normalized<CS: ConstraintSystem<E>>(&self, cs: &mut CS) -> Result<(), SynthesisError> {
         self.x.enforce_if_normalized(cs)?;
         self.y.enforce_if_normalized(cs)
     }
 
+    // the results of scalar multiplications are accumulators with possibly deeply unreduced coordinates:
+    // before being exposed (e.g. as public inputs) the coordinates should be brought into canonical form,
+    // so we normalize them (normalization enforces the range and the inequality with the modulus) and 
+    // return the limbs of the canonical representation
+    pub fn finalize_for_export<CS>(&mut self, cs: &mut CS) -> Result<Vec<Num<E>>, SynthesisError> 
+    where CS: ConstraintSystem<E> {
+        self.normalize_coordinates(cs)?;
+        self.get_raw_limbs_representation(cs)
+    }
+
+    // two points are equal if both are the point at infinity (their coordinates are meaningless then),
+    // or if neither of them is and their coordinates coincide.
+    // NB: we don't go through Boolean::enforce_equal, which panics on unequal witnesses
     pub fn enforce_equal<CS>(cs: &mut CS, this: &mut Self, other: &mut Self) -> Result<(), SynthesisError> 
     where CS: ConstraintSystem<E>
     {
-        FieldElement::enforce_equal(cs, &mut this.x, &mut other.x)?;
-        FieldElement::enforce_equal(cs, &mut this.y, &mut other.y)
+        let both_regular = this.is_infinity.is_constant() && !this.is_infinity.get_constant_value() &&
+            other.is_infinity.is_constant() && !other.is_infinity.get_constant_value();
+        if both_regular {
+            FieldElement::enforce_equal(cs, &mut this.x, &mut other.x)?;
+            return FieldElement::enforce_equal(cs, &mut this.y, &mut other.y);
+        }
+        match Self::equals(cs, this, other)? {
+            Boolean::Constant(flag) => if flag { Ok(()) } else { Err(SynthesisError::Unsatisfiable) },
+            ref flag => {
+                let mut minus_one = E::Fr::one();
+                minus_one.negate();
+                let mut lc = flag.lc(E::Fr::one());
+                lc.add_assign_constant(minus_one);
+                lc.enforce_zero(cs)
+            }
+        }
+    }
+
+    // uncompressed encoding x || y of the canonical coordinates, both big-endian 
+    // (as expected by Ethereum precompiles): ceil(bitlength / 8) bytes for every coordinate
+    pub fn to_bytes_uncompressed<CS>(&mut self, cs: &mut CS) -> Result<Vec<Num<E>>, SynthesisError> 
+    where CS: ConstraintSystem<E>
+    {
+        // point at infinity has no uncompressed encoding
+        match self.is_infinity {
+            Boolean::Constant(flag) => if flag { return Err(SynthesisError::Unsatisfiable) },
+            ref flag => flag.lc(E::Fr::one()).enforce_zero(cs)?,
+        }
+        let mut bytes = self.x.to_be_bytes(cs)?;
+        bytes.extend(self.y.to_be_bytes(cs)?);
+        Ok(bytes)
+    }
+
+    // decodes x || y and enforces that the point is on the curve: y^2 = x^3 + a * x + b
+    pub fn from_bytes_uncompressed<CS>(
+        cs: &mut CS, bytes: &[Num<E>], params: &'a RnsParameters<E, G::Base>
+    ) -> Result<Self, SynthesisError> 
+    where CS: ConstraintSystem<E>
+    {
+        // malformed encoding is an error rather than a panic: the length may come from untrusted input
+        if bytes.len() % 2 != 0 {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        let (x_bytes, y_bytes) = bytes.split_at(bytes.len() / 2);
+        let x = FieldElement::from_be_bytes(cs, x_bytes, params)?;
+        let y = FieldElement::from_be_bytes(cs, y_bytes, params)?;
+        Self::enforce_curve_equation(cs, &x, &y)?;
+
+        Ok(unsafe { Self::from_xy_unchecked(x, y) })
+    }
+
+    // inverse of get_raw_limbs_representation: the coordinates are range checked and enforced to be normalized,
+    // the curve equation is only enforced if requested (e.g. it is not needed if the limbs were produced by the 
+    // circuit itself and are just passed through transcript)
+    pub fn from_raw_limbs_representation<CS>(
+        cs: &mut CS, limbs: &[Num<E>], params: &'a RnsParameters<E, G::Base>, check_on_curve: bool
+    ) -> Result<Self, SynthesisError> 
+    where CS: ConstraintSystem<E>
+    {
+        let num_limbs = params.get_num_binary_limbs();
+        assert_eq!(limbs.len(), 2 * num_limbs, "invalid number of limbs");
+        let (x_limbs, y_limbs) = limbs.split_at(num_limbs);
+        let x = FieldElement::from_raw_limbs_representation(cs, x_limbs, params)?;
+        let y = FieldElement::from_raw_limbs_representation(cs, y_limbs, params)?;
+        if check_on_curve {
+            Self::enforce_curve_equation(cs, &x, &y)?;
+        }
+
+        Ok(unsafe { Self::from_xy_unchecked(x, y) })
+    }
+
+    // y^2 = x^3 + a * x + b
+    fn enforce_curve_equation<CS: ConstraintSystem<E>>(
+        cs: &mut CS, x: &FieldElement<'a, E, G::Base>, y: &FieldElement<'a, E, G::Base>
+    ) -> Result<(), SynthesisError> {
+        let params = x.representation_params;
+        let b = FieldElement::constant(G::b_coeff(), params);
+        let mut lhs = y.square(cs)?;
+        let x_squared = x.square(cs)?;
+        let x_cubed = x_squared.mul(cs, x)?;
+        let mut rhs = x_cubed.add(cs, &b)?;
+        if !G::a_coeff().is_zero() {
+            let a = FieldElement::constant(G::a_coeff(), params);
+            let a_x = a.mul(cs, x)?;
+            rhs = rhs.add(cs, &a_x)?;
+        }
+        FieldElement::enforce_equal(cs, &mut lhs, &mut rhs)
+    }
+
+    pub fn enforce_equals_constant<CS>(&mut self, cs: &mut CS, c: G) -> Result<(), SynthesisError> 
+    where CS: ConstraintSystem<E>
+    {
+        assert!(!c.is_zero(), "point at infinity has no affine coordinates");
+        let (x, y) = c.into_xy_unchecked();
+        self.x.enforce_equal_to_constant(cs, x)?;
+        self.y.enforce_equal_to_constant(cs, y)
+    }
+
+    pub fn equals_constant<CS>(&mut self, cs: &mut CS, c: G) -> Result<Boolean, SynthesisError> 
+    where CS: ConstraintSystem<E>
+    {
+        assert!(!c.is_zero(), "point at infinity has no affine coordinates");
+        let (x, y) = c.into_xy_unchecked();
+        let x_check = self.x.equals_constant(cs, x)?;
+        let y_check = self.y.equals_constant(cs, y)?;
+        Boolean::and(cs, &x_check, &y_check)
+    }
+
+    pub fn equals_generator<CS>(&mut self, cs: &mut CS) -> Result<Boolean, SynthesisError> 
+    where CS: ConstraintSystem<E>
+    {
+        self.equals_constant(cs, G::one())
     }
 
     pub fn equals<CS>(cs: &mut CS, this: &mut Self, other: &mut Self) -> Result<Boolean, SynthesisError> 
     where CS: ConstraintSystem<E>
     {
+        let both_infinity = Boolean::and(cs, &this.is_infinity, &other.is_infinity)?;
+        // both points are known to be at infinity: there is no need to compare the coordinates
+        if both_infinity.is_constant() && both_infinity.get_constant_value() {
+            return Ok(Boolean::constant(true));
+        }
+        let neither_infinity = Boolean::and(cs, &this.is_infinity.not(), &other.is_infinity.not())?;
+        if neither_infinity.is_constant() && !neither_infinity.get_constant_value() && both_infinity.is_constant() {
+            return Ok(Boolean::constant(false));
+        }
+
         let x_check = FieldElement::equals(cs, &mut this.x, &mut other.x)?;
         let y_check = FieldElement::equals(cs, &mut this.y, &mut other.y)?;
-        let equals = Boolean::and(cs, &x_check, &y_check)?;
+        let coordinates_equal = Boolean::and(cs, &x_check, &y_check)?;
+        let regular_and_equal = Boolean::and(cs, &neither_infinity, &coordinates_equal)?;
+        let equals = Boolean::or(cs, &both_infinity, &regular_and_equal)?;
         
         Ok(equals)
     }
 
+    // element-wise comparison of two slices of points, slices of different length are rejected with an error
+    pub fn batch_equals<CS>(cs: &mut CS, this: &mut [Self], other: &mut [Self]) -> Result<Boolean, SynthesisError> 
+    where CS: ConstraintSystem<E>
+    {
+        if this.len() != other.len() {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        let mut equals = Boolean::constant(true);
+        for (a, b) in this.iter_mut().zip(other.iter_mut()) {
+            let check = Self::equals(cs, a, b)?;
+            equals = Boolean::and(cs, &equals, &check)?;
+        }
+
+        Ok(equals)
+    }
+
     pub fn negate<CS: ConstraintSystem<E>>(&self, cs: &mut CS) -> Result<Self, SynthesisError> {
         let y_negated = self.y.negate(cs)?;
         let new_value = self.value.map(|x| {
@@ -181,9 +503,11 @@ impl<'a, E: Engine, G: GenericCurveAffine> AffinePoint<'a, E, G> where <G as Gen
         let new = Self {
             x: self.x.clone(),
             y: y_negated,
+            is_infinity: self.is_infinity.clone(),
             value: new_value
         };
 
+        new.debug_check_value_consistency();
         Ok(new)
     }
 
@@ -191,17 +515,23 @@ impl<'a, E: Engine, G: GenericCurveAffine> AffinePoint<'a, E, G> where <G as Gen
     where CS: ConstraintSystem<E>
     {
         let y_negated = self.y.conditionally_negate(cs, flag)?;
-        let new_value = self.value.map(|x| {
-            let mut tmp = x;
-            tmp.negate();
-            tmp
-        });
+        let new_value = match (flag.get_value(), self.value) {
+            (Some(true), Some(p)) => {
+                let mut tmp = p;
+                tmp.negate();
+                Some(tmp)
+            },
+            (Some(false), Some(p)) => Some(p),
+            (_, _) => None
+        };
         let new = Self {
             x: self.x.clone(),
             y: y_negated,
+            is_infinity: self.is_infinity.clone(),
             value: new_value
         };
 
+        new.debug_check_value_consistency();
         Ok(new)
     }
 
@@ -212,17 +542,101 @@ impl<'a, E: Engine, G: GenericCurveAffine> AffinePoint<'a, E, G> where <G as Gen
         let second_value = second.get_value();
         let x = FieldElement::conditionally_select(cs, flag, &first.x, &second.x)?;
         let y = FieldElement::conditionally_select(cs, flag, &first.y, &second.y)?;
+        let is_infinity = Boolean::conditionally_select(cs, flag, &first.is_infinity, &second.is_infinity)?;
 
         let value = match (flag.get_value(), first_value, second_value) {
             (Some(true), Some(p), _) => Some(p),
             (Some(false), _, Some(p)) => Some(p),
             (_, _, _) => None
         };
-        let selected = AffinePoint { x, y, value };
+        let selected = AffinePoint { x, y, is_infinity, value };
+
+        selected.debug_check_value_consistency();
+        Ok(selected)
+    }
+
+    // selects table[index] where index = \sum bits[i] * 2^i (little-endian bits), as required by the inner loop
+    // of windowed multiplication; coordinates are selected via FieldElement::select_from_table, which is cheaper
+    // than the tree of nested select calls
+    pub fn select_from_table<CS>(cs: &mut CS, bits: &[Boolean], table: &[Self]) -> Result<Self, SynthesisError>
+    where CS: ConstraintSystem<E>
+    {
+        assert!(table.len() > 0 && table.len() == 1 << bits.len(), "table size should be 2^(number of bits)");
+        let xs: Vec<_> = table.iter().map(|p| p.x.clone()).collect();
+        let ys: Vec<_> = table.iter().map(|p| p.y.clone()).collect();
+        let x = FieldElement::select_from_table(cs, bits, &xs)?;
+        let y = FieldElement::select_from_table(cs, bits, &ys)?;
+
+        let mut flags: Vec<_> = table.iter().map(|p| p.is_infinity.clone()).collect();
+        for bit in bits.iter() {
+            flags = flags.chunks(2).map(|pair| {
+                Boolean::conditionally_select(cs, bit, &pair[1], &pair[0])
+            }).collect::<Result<Vec<_>, SynthesisError>>()?;
+        }
+        let is_infinity = flags.pop().unwrap();
+
+        let index = bits.iter().rev().try_fold(0usize, |acc, bit| bit.get_value().map(|b| (acc << 1) | (b as usize)));
+        let value = index.and_then(|idx| table[idx].get_value());
+        let selected = AffinePoint { x, y, is_infinity, value };
 
+        selected.debug_check_value_consistency();
         Ok(selected)
     }
 
+    // computes +/- (select_flag ? first : second) with the sign given by negate_flag:
+    // x coordinate and infinity flag are selected, the y coordinate is selected and conditionally negated
+    pub fn select_or_negate<CS>(
+        cs: &mut CS, select_flag: &Boolean, negate_flag: &Boolean, first: &Self, second: &Self
+    ) -> Result<Self, SynthesisError>
+    where CS: ConstraintSystem<E>
+    {
+        let x = FieldElement::conditionally_select(cs, select_flag, &first.x, &second.x)?;
+        let y = FieldElement::conditionally_select(cs, select_flag, &first.y, &second.y)?;
+        let y = y.conditionally_negate(cs, negate_flag)?;
+        let is_infinity = Boolean::conditionally_select(cs, select_flag, &first.is_infinity, &second.is_infinity)?;
+
+        let selected_value = match (select_flag.get_value(), first.get_value(), second.get_value()) {
+            (Some(true), Some(p), _) => Some(p),
+            (Some(false), _, Some(p)) => Some(p),
+            (_, _, _) => None
+        };
+        let value = match (negate_flag.get_value(), selected_value) {
+            (Some(true), Some(p)) => {
+                let mut tmp = p;
+                tmp.negate();
+                Some(tmp)
+            },
+            (Some(false), Some(p)) => Some(p),
+            (_, _) => None
+        };
+
+        let res = AffinePoint { x, y, is_infinity, value };
+        res.debug_check_value_consistency();
+        Ok(res)
+    }
+
+    // in-place analogue of select(flag, new, self): the unchanged branch is never cloned
+    pub fn conditionally_replace<CS>(&mut self, cs: &mut CS, flag: &Boolean, new: &Self) -> Result<(), SynthesisError>
+    where CS: ConstraintSystem<E>
+    {
+        if let Boolean::Constant(flag) = flag {
+            if *flag { *self = new.clone(); }
+            return Ok(());
+        }
+
+        self.x = FieldElement::conditionally_select(cs, flag, &new.x, &self.x)?;
+        self.y = FieldElement::conditionally_select(cs, flag, &new.y, &self.y)?;
+        self.is_infinity = Boolean::conditionally_select(cs, flag, &new.is_infinity, &self.is_infinity)?;
+        self.value = match (flag.get_value(), new.value, self.value) {
+            (Some(true), Some(p), _) => Some(p),
+            (Some(false), _, Some(p)) => Some(p),
+            (_, _, _) => None
+        };
+        self.debug_check_value_consistency();
+
+        Ok(())
+    }
+
     #[track_caller]
     pub fn is_on_curve_for_zero_a<CS: ConstraintSystem<E>>(&self, cs: &mut CS, curve_b: G::Base
     ) -> Result<Boolean, SynthesisError> {
@@ -242,6 +656,13 @@ impl<'a, E: Engine, G: GenericCurveAffine> AffinePoint<'a, E, G> where <G as Gen
     pub fn add_unequal<CS>(&mut self, cs: &mut CS, other: &mut Self) -> Result<Self, SynthesisError> 
     where CS: ConstraintSystem<E>
     {
+        // zero-cost path is taken if none of the points may be at infinity, otherwise use complete addition law
+        match (&self.is_infinity, &other.is_infinity) {
+            (Boolean::Constant(false), Boolean::Constant(false)) => {},
+            (Boolean::Constant(true), _) => return Ok(other.clone()),
+            (_, Boolean::Constant(true)) => return Ok(self.clone()),
+            _ => return self.add_complete(cs, other),
+        };
         // only enforce that x != x'
         FieldElement::enforce_not_equal(cs, &mut self.x, &mut other.x)?;
         self.add_unequal_unchecked(cs, other)
@@ -287,18 +708,108 @@ impl<'a, E: Engine, G: GenericCurveAffine> AffinePoint<'a, E, G> where <G as Gen
             _ => None
         };
    
-        let new = Self {
+        let mut new = Self {
             x: new_x,
             y: new_y,
+            is_infinity: Boolean::constant(false),
             value: new_value
         };
+        new.apply_output_reduction_policy(cs)?;
+        new.debug_check_value_consistency();
+        Ok(new)
+    }
+
+    // returns self + other if flag is set and self otherwise. The denominator x' - x is replaced by one
+    // for the unset flag, so coinciding x coordinates are harmless in this case (while for the set flag
+    // the circuit is unsatisfiable as for add_unequal_unchecked). If x coordinates coincide in the witness
+    // the value of the result is None regardless of the flag: the chord is meaningless for such points
+    pub fn conditionally_add_unequal_unchecked<CS>(
+        &self, cs: &mut CS, flag: &Boolean, other: &Self
+    ) -> Result<Self, SynthesisError>
+    where CS: ConstraintSystem<E>
+    {
+        let params = self.x.representation_params;
+        let other_x_minus_this_x = other.x.sub(cs, &self.x)?;
+        let safe_den = FieldElement::conditionally_select(
+            cs, flag, &other_x_minus_this_x, &FieldElement::one(params)
+        )?;
+        let mut chain = FieldElementsChain::new();
+        chain.add_pos_term(&other.y).add_neg_term(&self.y);
+        let lambda = FieldElement::div_with_chain(cs, chain, &safe_den)?;
+
+        let mut chain = FieldElementsChain::new();
+        chain.add_neg_term(&other.x).add_neg_term(&self.x);
+        let new_x = lambda.square_with_chain(cs, chain)?;
+
+        let this_x_minus_new_x = self.x.sub(cs, &new_x)?;
+        let mut chain = FieldElementsChain::new();
+        chain.add_neg_term(&self.y);
+        let new_y = FieldElement::mul_with_chain(cs, &lambda, &this_x_minus_new_x, chain)?;
+
+        let x = FieldElement::conditionally_select(cs, flag, &new_x, &self.x)?;
+        let y = FieldElement::conditionally_select(cs, flag, &new_y, &self.y)?;
+        let is_infinity = Boolean::conditionally_select(cs, flag, &Boolean::constant(false), &self.is_infinity)?;
+
+        let new_value = match (flag.get_value(), self.value, other.value) {
+            (_, Some(this), Some(other)) if this.into_xy_unchecked().0 == other.into_xy_unchecked().0 => None,
+            (Some(true), Some(this), Some(other)) => {
+                let mut tmp = this.into_projective();
+                tmp.add_assign_mixed(&other);
+                Some(tmp.into_affine())
+            },
+            (Some(false), Some(this), Some(_)) => Some(this),
+            _ => None
+        };
+
+        let mut new = Self { x, y, is_infinity, value: new_value };
+        new.apply_output_reduction_policy(cs)?;
+        new.debug_check_value_consistency();
         Ok(new)
     }
 
+    // enforces that p + q = r without computing the sum (and hence without division):
+    // for lambda = (y_q - y_p) / (x_q - x_p) we have x_r = lambda^2 - x_p - x_q and 
+    // y_r = lambda * (x_p - x_r) - y_p, which after multiplying by the denominator are equivalent to:
+    // (x_q - x_p)^2 * (x_r + x_p + x_q) = (y_q - y_p)^2 and (x_q - x_p) * (y_r + y_p) = (y_q - y_p) * (x_p - x_r)
+    // as for add_unequal the relation only makes sense for x_p != x_q (which is enforced), so doubling (p = q) 
+    // and p = -q (sum at infinity) are not covered; points at infinity are not allowed as well
+    #[track_caller]
+    pub fn enforce_addition<CS>(cs: &mut CS, p: &mut Self, q: &mut Self, r: &Self) -> Result<(), SynthesisError> 
+    where CS: ConstraintSystem<E>
+    {
+        for pt in [&*p, &*q, r].iter() {
+            assert!(pt.is_infinity.get_value() != Some(true), "points at infinity are not supported");
+        }
+        FieldElement::enforce_not_equal(cs, &mut p.x, &mut q.x)?;
+
+        let dx = q.x.sub(cs, &p.x)?;
+        let dy = q.y.sub(cs, &p.y)?;
+
+        // (x_q - x_p)^2 * (x_r + x_p + x_q) = (y_q - y_p)^2
+        let dx_squared = dx.square(cs)?;
+        let mut dy_squared = dy.square(cs)?;
+        let x_sum = r.x.add(cs, &p.x)?.add(cs, &q.x)?;
+        let mut lhs = dx_squared.mul(cs, &x_sum)?;
+        FieldElement::enforce_equal(cs, &mut lhs, &mut dy_squared)?;
+
+        // (x_q - x_p) * (y_r + y_p) = (y_q - y_p) * (x_p - x_r)
+        let y_sum = r.y.add(cs, &p.y)?;
+        let x_diff = p.x.sub(cs, &r.x)?;
+        let mut lhs = dx.mul(cs, &y_sum)?;
+        let mut rhs = dy.mul(cs, &x_diff)?;
+        FieldElement::enforce_equal(cs, &mut lhs, &mut rhs)
+    }
+
     #[track_caller]
     pub fn sub_unequal<CS>(&mut self, cs: &mut CS, other: &mut Self) -> Result<Self, SynthesisError> 
     where CS: ConstraintSystem<E>
     {
+        match (&self.is_infinity, &other.is_infinity) {
+            (Boolean::Constant(false), Boolean::Constant(false)) => {},
+            (Boolean::Constant(true), _) => return other.negate(cs),
+            (_, Boolean::Constant(true)) => return Ok(self.clone()),
+            _ => return self.sub_complete(cs, other),
+        };
         // only enforce that x != x'
         FieldElement::enforce_not_equal(cs, &mut self.x, &mut other.x)?;
         self.sub_unequal_unchecked(cs, other)
@@ -344,22 +855,51 @@ impl<'a, E: Engine, G: GenericCurveAffine> AffinePoint<'a, E, G> where <G as Gen
             _ => None
         };
    
-        let new = Self {
+        let mut new = Self {
             x: new_x,
             y: new_y,
+            is_infinity: Boolean::constant(false),
             value: new_value
         };
+        new.apply_output_reduction_policy(cs)?;
+        new.debug_check_value_consistency();
         Ok(new)
     }
 
     #[track_caller]
     pub fn double<CS: ConstraintSystem<E>>(&self, cs: &mut CS) -> Result<Self, SynthesisError> {
-        // this formula is only valid for curve with zero j-ivariant
-        assert!(G::a_coeff().is_zero());
+        match self.is_infinity {
+            Boolean::Constant(false) => {},
+            Boolean::Constant(true) => return Ok(self.clone()),
+            _ => {
+                // the coordinates of the point at infinity are meaningless (and may lead to division by zero),
+                // so we double the regular point instead and select the point at infinity back afterwards
+                let params = self.x.representation_params;
+                let mut safe = Self::select(cs, &self.is_infinity, &Self::constant(G::one(), params), self)?;
+                safe.is_infinity = Boolean::constant(false);
+                let doubled = safe.double(cs)?;
+                let mut res = Self::select(cs, &self.is_infinity, self, &doubled)?;
+                res.is_infinity = self.is_infinity.clone();
+                return Ok(res);
+            }
+        };
 
         let x_squared = self.x.square(cs)?;
+        self.double_with_x_squared(cs, &x_squared)
+    }
+
+    // doubling of the regular point for x^2 computed elsewhere (e.g. shared with the curve equation)
+    fn double_with_x_squared<CS: ConstraintSystem<E>>(
+        &self, cs: &mut CS, x_squared: &FieldElement<'a, E, G::Base>
+    ) -> Result<Self, SynthesisError> {
+        // lambda = (3 * x^2 + a) / (2 * y): curve coefficient a is folded into the numerator chain as a constant
+        let params = self.x.representation_params;
         let mut chain = FieldElementsChain::new();
-        chain.add_pos_term(&x_squared).add_pos_term(&x_squared).add_pos_term(&x_squared);
+        chain.add_pos_term(x_squared).add_pos_term(x_squared).add_pos_term(x_squared);
+        let a = FieldElement::constant(G::a_coeff(), params);
+        if !G::a_coeff().is_zero() {
+            chain.add_pos_term(&a);
+        }
         let two_y = self.y.double(cs)?;
         let lambda = FieldElement::div_with_chain(cs, chain, &two_y)?;
 
@@ -378,11 +918,14 @@ impl<'a, E: Engine, G: GenericCurveAffine> AffinePoint<'a, E, G> where <G as Gen
             tmp.into_affine()
         });
         
-        let new = Self {
+        let mut new = Self {
             x: new_x,
             y: new_y,
+            is_infinity: Boolean::constant(false),
             value: new_value
         };
+        new.apply_output_reduction_policy(cs)?;
+        new.debug_check_value_consistency();
         Ok(new)
     }
 
@@ -396,6 +939,17 @@ impl<'a, E: Engine, G: GenericCurveAffine> AffinePoint<'a, E, G> where <G as Gen
         // we can also try https://eprint.iacr.org/2015/1060.pdf
         // only check that x - x' != 0 and go into the unchecked routine
         FieldElement::enforce_not_equal(cs, &mut self.x, &mut other.x)?;
+        // the second step (P + Q) + P is a chord addition as well: it requires x(P + Q) != x(P), which fails iff Q = -2P.
+        // The constraints are still sound in that case (there is no witness for the division), 
+        // but we would like to report it explicitly rather than fail somewhere inside of the division
+        if let (Some(this), Some(other_value)) = (self.value, other.value) {
+            let mut intermediate = this.into_projective();
+            intermediate.add_assign_mixed(&other_value);
+            let intermediate = intermediate.into_affine();
+            if !intermediate.is_zero() && intermediate.into_xy_unchecked().0 == this.into_xy_unchecked().0 {
+                return Err(SynthesisError::Unsatisfiable);
+            }
+        }
         self.double_and_add_unchecked(cs, &other)
     }
 
@@ -438,84 +992,367 @@ impl<'a, E: Engine, G: GenericCurveAffine> AffinePoint<'a, E, G> where <G as Gen
             _ => None
         };
    
-        let new = Self {
+        let mut new = Self {
             x: new_x,
             y: new_y,
+            is_infinity: Boolean::constant(false),
             value: new_value
         };
+        new.apply_output_reduction_policy(cs)?;
+        new.debug_check_value_consistency();
         Ok(new)
     }
 }
 
 
-// we are particularly interested in three curves: secp256k1, bn256 and bls12-281
-// unfortunately, only bls12-381 has a cofactor
-impl<'a, E: Engine, G: GenericCurveAffine + rand::Rand> AffinePoint<'a, E, G> where <G as GenericCurveAffine>::Base: PrimeField {
+impl<'a, E: Engine, G: GenericCurveAffine> AffinePoint<'a, E, G> where <G as GenericCurveAffine>::Base: PrimeField {
+    // complete addition law: handles P + Q, P + P, P + (-P) and additions with point at infinity.
+    // we compute both chord and tangent lambdas in the single division:
+    // lambda = (y' - y) / (x' - x) if x != x' and lambda = (3 * x^2 + a) / (2 * y) otherwise,
+    // if the denominator is zero we replace it by one: the result is then a point at infinity anyway
     #[track_caller]
-    pub fn mul_by_scalar_for_composite_order_curve<CS: ConstraintSystem<E>>(
-        &mut self, cs: &mut CS, scalar: &mut FieldElement<'a, E, G::Scalar>
-    ) -> Result<Self, SynthesisError> {
-        if let Some(value) = scalar.get_field_value() {
-            assert!(!value.is_zero(), "can not multiply by zero in the current approach");
-        }
-        if scalar.is_constant() {
-            unimplemented!();
-        }
-       
+    pub fn add_complete<CS>(&mut self, cs: &mut CS, other: &mut Self) -> Result<Self, SynthesisError> 
+    where CS: ConstraintSystem<E>
+    {
         let params = self.x.representation_params;
-        let entries = scalar.decompose_into_skewed_representation(cs)?;
-       
-        // we add a random point to the accumulator to avoid having zero anywhere (with high probability)
-        // and unknown discrete log allows us to be "safe"
-        let offset_generator = crate::constants::make_random_points_with_unknown_discrete_log::<G>(
-            &crate::constants::MULTIEXP_DST[..], 1
-        )[0];
-        let mut generator = Self::constant(offset_generator, params);
-        let mut acc = self.add_unequal(cs, &mut generator)?;
-
-        let entries_without_first_and_last = &entries[1..(entries.len() - 1)];
-        let mut num_doubles = 0;
-
-        let mut x = self.x.clone();
-        let mut minus_y = self.y.negate(cs)?;
-        minus_y.reduce_loose(cs)?;
-
-        for e in entries_without_first_and_last.iter() {
-            let selected_y = FieldElement::conditionally_select(cs, e, &minus_y, &self.y)?;  
-            let t_value = match (self.value, e.get_value()) {
-                (Some(val), Some(bit)) => {
-                    let mut val = val;
-                    if bit {
-                        val.negate();
-                    }
-                    Some(val)
-                },
-                _ => None
-            };
-            let mut t = Self {
-                x: x,
-                y: selected_y,
-                value: t_value
-            };
+        let x_are_equal = FieldElement::equals(cs, &mut self.x, &mut other.x)?;
+        let y_are_equal = FieldElement::equals(cs, &mut self.y, &mut other.y)?;
 
-            acc = acc.double_and_add(cs, &mut t)?;
-            num_doubles += 1;
-            x = t.x;
+        // chord: (y' - y) / (x' - x)
+        let chord_num = other.y.sub(cs, &self.y)?;
+        let chord_den = other.x.sub(cs, &self.x)?;
+        
+        // tangent: (3 * x^2 + a) / (2 * y)
+        let x_squared = self.x.square(cs)?;
+        let two_x_squared = x_squared.double(cs)?;
+        let mut tangent_num = two_x_squared.add(cs, &x_squared)?;
+        if !G::a_coeff().is_zero() {
+            let a = FieldElement::constant(G::a_coeff(), params);
+            tangent_num = tangent_num.add(cs, &a)?;
         }
+        let tangent_den = self.y.double(cs)?;
 
-        let with_skew = acc.sub_unequal(cs, &mut self.clone())?;
-        let last_entry = entries.last().unwrap();
+        let num = FieldElement::conditionally_select(cs, &x_are_equal, &tangent_num, &chord_num)?;
+        let mut den = FieldElement::conditionally_select(cs, &x_are_equal, &tangent_den, &chord_den)?;
+        let den_is_zero = den.is_zero(cs)?;
+        let safe_den = FieldElement::conditionally_select(cs, &den_is_zero, &FieldElement::one(params), &den)?;
+        let lambda = num.div(cs, &safe_den)?;
 
-        let with_skew_value = with_skew.get_value();
-        let with_skew_x = with_skew.x;
-        let with_skew_y = with_skew.y;
+        // lambda^2 + (-x' - x)
+        let mut chain = FieldElementsChain::new();
+        chain.add_neg_term(&other.x).add_neg_term(&self.x);
+        let new_x = lambda.square_with_chain(cs, chain)?;
 
-        let acc_value = acc.get_value();
-        let acc_x = acc.x;
-        let acc_y = acc.y;
+        // lambda * (x - new_x) + (- y)
+        let this_x_minus_new_x = self.x.sub(cs, &new_x)?;
+        let mut chain = FieldElementsChain::new();
+        chain.add_neg_term(&self.y);
+        let new_y = FieldElement::mul_with_chain(cs, &lambda, &this_x_minus_new_x, chain)?;
 
-        let final_value = match (with_skew_value, acc_value, last_entry.get_value()) {
-            (Some(s_value), Some(a_value), Some(b)) => {
+        // if both points are regular the sum is at infinity iff P = -Q (or P = Q is of order two)
+        let is_opposite = Boolean::and(cs, &x_are_equal, &y_are_equal.not())?;
+        let sum_is_infinity = Boolean::or(cs, &is_opposite, &den_is_zero)?;
+
+        // now take care of the inputs at infinity: O + Q = Q, P + O = P
+        let x = FieldElement::conditionally_select(cs, &other.is_infinity, &self.x, &new_x)?;
+        let x = FieldElement::conditionally_select(cs, &self.is_infinity, &other.x, &x)?;
+        let y = FieldElement::conditionally_select(cs, &other.is_infinity, &self.y, &new_y)?;
+        let y = FieldElement::conditionally_select(cs, &self.is_infinity, &other.y, &y)?;
+        let is_infinity = Boolean::conditionally_select(cs, &other.is_infinity, &self.is_infinity, &sum_is_infinity)?;
+        let is_infinity = Boolean::conditionally_select(cs, &self.is_infinity, &other.is_infinity, &is_infinity)?;
+        
+        let new_value = match (self.value, other.value) {
+            (Some(this), Some(other)) => {
+                let mut tmp = this.into_projective();
+                tmp.add_assign_mixed(&other);
+                Some(tmp.into_affine())
+            },
+            _ => None
+        };
+
+        let mut new = Self {
+            x,
+            y,
+            is_infinity,
+            value: new_value
+        };
+        new.apply_output_reduction_policy(cs)?;
+        new.debug_check_value_consistency();
+        Ok(new)
+    }
+
+    // generic addition for accumulation: the inputs may be equal, opposite or at infinity. Points known
+    // to be at infinity at synthesis time are handled for free, otherwise we go through the complete addition law:
+    // its cost doesn't depend on the inputs (two equality checks, one division, two multiplications, one squaring 
+    // and a constant number of selections), so it is bounded by a small multiple of the cost of add_unequal
+    #[track_caller]
+    pub fn add<CS>(&mut self, cs: &mut CS, other: &mut Self) -> Result<Self, SynthesisError> 
+    where CS: ConstraintSystem<E>
+    {
+        match (&self.is_infinity, &other.is_infinity) {
+            (Boolean::Constant(true), _) => Ok(other.clone()),
+            (_, Boolean::Constant(true)) => Ok(self.clone()),
+            _ => self.add_complete(cs, other),
+        }
+    }
+
+    // P - Q = P + (-Q) via complete addition law
+    #[track_caller]
+    pub fn sub_complete<CS>(&mut self, cs: &mut CS, other: &mut Self) -> Result<Self, SynthesisError> 
+    where CS: ConstraintSystem<E>
+    {
+        let mut other_negated = other.negate(cs)?;
+        self.add_complete(cs, &mut other_negated)
+    }
+
+    // sums arbitrary points (possibly equal, opposite or at infinity) using complete addition law,
+    // the sum of an empty slice is the point at infinity
+    pub fn sum_complete<CS: ConstraintSystem<E>>(
+        cs: &mut CS, points: &[Self], params: &'a RnsParameters<E, G::Base>
+    ) -> Result<AffinePointWithInfinity<'a, E, G>, SynthesisError> {
+        let mut iter = points.iter();
+        let mut acc = match iter.next() {
+            Some(first) => first.clone(),
+            None => return Ok(Self::zero(params)) 
+        };
+        for point in iter {
+            acc = acc.add_complete(cs, &mut point.clone())?;
+        }
+
+        Ok(acc)
+    }
+
+    // EC half of BLS aggregation: the aggregated public key (or signature) is the sum of all the 
+    // individual ones, keys may coincide, so complete addition law is used
+    pub fn aggregate<CS: ConstraintSystem<E>>(
+        cs: &mut CS, points: &[Self]
+    ) -> Result<AffinePointWithInfinity<'a, E, G>, SynthesisError> {
+        if points.is_empty() {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        let params = points[0].x.representation_params;
+        Self::sum_complete(cs, points, params)
+    }
+}
+
+
+// we are particularly interested in three curves: secp256k1, bn256 and bls12-281
+// unfortunately, only bls12-381 has a cofactor
+impl<'a, E: Engine, G: GenericCurveAffine + rand::Rand> AffinePoint<'a, E, G> where <G as GenericCurveAffine>::Base: PrimeField {
+    #[track_caller]
+    pub fn mul_by_scalar_for_composite_order_curve<CS: ConstraintSystem<E>>(
+        &mut self, cs: &mut CS, scalar: &mut FieldElement<'a, E, G::Scalar>
+    ) -> Result<Self, SynthesisError> {
+        self.mul_by_scalar_for_composite_order_curve_ext(cs, scalar, false)
+    }
+
+    // inversions are cheap in our gate model, so the accumulator is affine by default: the projective one
+    // is left for experiments with other configurations (see test_composite_order_ladder_accumulators)
+    #[track_caller]
+    pub fn mul_by_scalar_for_composite_order_curve_ext<CS: ConstraintSystem<E>>(
+        &mut self, cs: &mut CS, scalar: &mut FieldElement<'a, E, G::Scalar>, use_projective_accumulator: bool
+    ) -> Result<Self, SynthesisError> {
+        let offset_generator = Self::offset_generator_from_dst(&crate::constants::MULTIEXP_DST[..]);
+        self.mul_by_scalar_for_composite_order_curve_impl(cs, scalar, use_projective_accumulator, offset_generator)
+    }
+
+    // generic multiplication: dispatches to mul_by_scalar_for_prime_order_curve (with the result converted back 
+    // to affine form) or to the composite-order ladder depending on the curve parameters. Zero scalar and the point 
+    // at infinity are both allowed and lead to the point at infinity: they are replaced by safe values for 
+    // the multiplication itself and the result is selected afterwards.
+    // alloc doesn't check that the point is in the prime order subgroup, so we do it here (this is a no-op for 
+    // prime order curves) unless the caller knows it for sure, e.g. if the point is the result of another 
+    // multiplication or was already checked
+    #[track_caller]
+    pub fn mul_by_scalar<CS: ConstraintSystem<E>>(
+        &mut self, cs: &mut CS, scalar: &mut FieldElement<'a, E, G::Scalar>, 
+        curve_params: &CurveCircuitParameters<E, G>, assume_in_subgroup: bool
+    ) -> Result<AffinePointWithInfinity<'a, E, G>, SynthesisError> {
+        let params = self.x.representation_params;
+        let identity = Self::zero(params);
+        if let Boolean::Constant(true) = self.is_infinity {
+            return Ok(identity);
+        }
+        let input_is_infinity = self.is_infinity.clone();
+        let mut safe_point = if input_is_infinity.is_constant() {
+            self.clone()
+        } else {
+            let mut tmp = Self::select(cs, &input_is_infinity, &Self::constant(G::one(), params), self)?;
+            tmp.is_infinity = Boolean::constant(false);
+            tmp
+        };
+
+        if !assume_in_subgroup {
+            safe_point.enforce_if_in_subgroup(cs, curve_params)?;
+        }
+
+        let scalar_is_zero = if scalar.is_constant() {
+            Boolean::constant(scalar.get_field_value().unwrap().is_zero())
+        } else {
+            scalar.is_zero(cs)?
+        };
+        if let Boolean::Constant(true) = scalar_is_zero {
+            return Ok(identity);
+        }
+        let scalar_params = scalar.representation_params;
+        let mut safe_scalar = FieldElement::conditionally_select(
+            cs, &scalar_is_zero, &FieldElement::one(scalar_params), scalar
+        )?;
+
+        // projective arithmetic covers arbitrary a (e.g. secp256r1), so any prime order curve may go this way
+        let res = if curve_params.is_prime_order_curve {
+            let mut projective = safe_point.mul_by_scalar_for_prime_order_curve(cs, &mut safe_scalar)?;
+            let (res, _) = projective.convert_to_affine_or_default(cs, &identity)?;
+            res
+        } else {
+            safe_point.mul_by_scalar_for_composite_order_curve_with_params(cs, &mut safe_scalar, curve_params)?
+        };
+
+        let result_is_infinity = Boolean::or(cs, &input_is_infinity, &scalar_is_zero)?;
+        Self::select(cs, &result_is_infinity, &identity, &res)
+    }
+
+    // the same as mul_by_scalar_for_composite_order_curve, but the offset generator is derived from the DST
+    // of the curve parameters, so that all the multiplications of the circuit agree on it
+    #[track_caller]
+    pub fn mul_by_scalar_for_composite_order_curve_with_params<CS: ConstraintSystem<E>>(
+        &mut self, cs: &mut CS, scalar: &mut FieldElement<'a, E, G::Scalar>, curve_params: &CurveCircuitParameters<E, G>
+    ) -> Result<Self, SynthesisError> {
+        let offset_generator = curve_params.offset_generator();
+        self.mul_by_scalar_for_composite_order_curve_impl(cs, scalar, false, offset_generator)
+    }
+
+    #[track_caller]
+    fn mul_by_scalar_for_composite_order_curve_impl<CS: ConstraintSystem<E>>(
+        &mut self, cs: &mut CS, scalar: &mut FieldElement<'a, E, G::Scalar>, 
+        use_projective_accumulator: bool, offset_generator: G
+    ) -> Result<Self, SynthesisError> {
+        if scalar.is_constant() {
+            let value = scalar.get_field_value().unwrap();
+            // trivial multiplications do not require any gates
+            if value.is_zero() {
+                return Ok(Self::zero(self.x.representation_params));
+            }
+            if value == G::Scalar::one() {
+                return Ok(self.clone());
+            }
+            return self.mul_by_biguint_constant(cs, &fe_to_biguint(&value));
+        }
+        if let Some(value) = scalar.get_field_value() {
+            assert!(!value.is_zero(), "can not multiply by zero in the current approach");
+        }
+       
+        let entries = scalar.decompose_into_skewed_representation(cs)?;
+        if use_projective_accumulator {
+            self.skewed_ladder_with_projective_accumulator(cs, &entries, offset_generator)
+        } else {
+            self.mul_by_skewed_digits_with_offset_generator(cs, &entries, offset_generator)
+        }
+    }
+
+    // the ladder of mul_by_scalar_for_composite_order_curve driven by an already computed skewed
+    // decomposition of the scalar (as returned by FieldElement::decompose_into_skewed_representation),
+    // so that the decomposition may be shared between several multiplications
+    #[track_caller]
+    pub fn mul_by_skewed_digits<CS: ConstraintSystem<E>>(
+        &mut self, cs: &mut CS, digits: &[Boolean]
+    ) -> Result<Self, SynthesisError> {
+        let offset_generator = Self::offset_generator_from_dst(&crate::constants::MULTIEXP_DST[..]);
+        self.mul_by_skewed_digits_with_offset_generator(cs, digits, offset_generator)
+    }
+
+    // the offset generator is hashed to the curve from the domain separation tag (with a fixed public seed),
+    // so the same DST always yields the same point and hence the same circuit
+    pub fn offset_generator_from_dst(dst: &[u8]) -> G {
+        crate::constants::make_random_points_with_unknown_discrete_log::<G>(dst, 1)[0]
+    }
+
+    // the same as mul_by_skewed_digits, but with explicitly provided offset generator: the caller is responsible 
+    // for the discrete log of the offset being unknown, so this is mostly useful for testing
+    #[track_caller]
+    pub fn mul_by_skewed_digits_with_offset_generator<CS: ConstraintSystem<E>>(
+        &mut self, cs: &mut CS, digits: &[Boolean], offset_generator: G
+    ) -> Result<Self, SynthesisError> {
+        self.skewed_ladder(cs, digits, offset_generator, false)
+    }
+
+    // the variant of the ladder which negates y coordinate in place for every digit instead of 
+    // selecting between precomputed y and -y: the selection is the same, but every digit pays for its own 
+    // negation on top of it, so this is more expensive (see test_skewed_ladder_negation_strategies)
+    #[track_caller]
+    pub fn mul_by_skewed_digits_with_conditional_negation<CS: ConstraintSystem<E>>(
+        &mut self, cs: &mut CS, digits: &[Boolean]
+    ) -> Result<Self, SynthesisError> {
+        let offset_generator = Self::offset_generator_from_dst(&crate::constants::MULTIEXP_DST[..]);
+        self.skewed_ladder(cs, digits, offset_generator, true)
+    }
+
+    #[track_caller]
+    fn skewed_ladder<CS: ConstraintSystem<E>>(
+        &mut self, cs: &mut CS, digits: &[Boolean], offset_generator: G, negate_in_place: bool
+    ) -> Result<Self, SynthesisError> {
+        assert!(digits.len() > 2, "skewed decomposition should contain at least the first and the last entries");
+        assert!(!offset_generator.is_zero(), "offset generator can not be the point at infinity");
+        let params = self.x.representation_params;
+        let entries = digits;
+       
+        // we add a random point to the accumulator to avoid having zero anywhere (with high probability)
+        // and unknown discrete log allows us to be "safe"
+        let mut generator = Self::constant(offset_generator, params);
+        let mut acc = self.add_unequal(cs, &mut generator)?;
+
+        let entries_without_first_and_last = &entries[1..(entries.len() - 1)];
+        let mut num_doubles = 0;
+
+        let mut x = self.x.clone();
+        let minus_y = if negate_in_place {
+            None
+        } else {
+            let mut minus_y = self.y.negate(cs)?;
+            minus_y.prepare_for_select(cs)?;
+            Some(minus_y)
+        };
+
+        for e in entries_without_first_and_last.iter() {
+            let selected_y = match minus_y {
+                Some(ref minus_y) => FieldElement::conditionally_select(cs, e, minus_y, &self.y)?,
+                None => self.y.conditionally_negate(cs, e)?
+            };
+            let t_value = match (self.value, e.get_value()) {
+                (Some(val), Some(bit)) => {
+                    let mut val = val;
+                    if bit {
+                        val.negate();
+                    }
+                    Some(val)
+                },
+                _ => None
+            };
+            let mut t = Self {
+                x: x,
+                y: selected_y,
+                is_infinity: Boolean::constant(false),
+                value: t_value
+            };
+
+            acc = acc.double_and_add(cs, &mut t)?;
+            num_doubles += 1;
+            x = t.x;
+        }
+
+        let with_skew = acc.sub_unequal(cs, &mut self.clone())?;
+        let last_entry = entries.last().unwrap();
+
+        let with_skew_value = with_skew.get_value();
+        let with_skew_x = with_skew.x;
+        let with_skew_y = with_skew.y;
+
+        let acc_value = acc.get_value();
+        let acc_x = acc.x;
+        let acc_y = acc.y;
+
+        let final_value = match (with_skew_value, acc_value, last_entry.get_value()) {
+            (Some(s_value), Some(a_value), Some(b)) => {
                 if b {
                     Some(s_value)
                 } else {
@@ -525,60 +1362,2378 @@ impl<'a, E: Engine, G: GenericCurveAffine + rand::Rand> AffinePoint<'a, E, G> wh
             _ => None
         };
 
-        let final_acc_x = FieldElement::conditionally_select(cs, last_entry, &with_skew_x, &acc_x)?;
-        let final_acc_y = FieldElement::conditionally_select(cs, last_entry, &with_skew_y, &acc_y)?;
+        let final_acc_x = FieldElement::conditionally_select(cs, last_entry, &with_skew_x, &acc_x)?;
+        let final_acc_y = FieldElement::conditionally_select(cs, last_entry, &with_skew_y, &acc_y)?;
+
+        let mut scaled_offset = offset_generator.into_projective();
+        for _ in 0..num_doubles {
+            scaled_offset.double();
+        }
+        let mut offset = Self::constant(scaled_offset.into_affine(), params);
+
+        let mut result = Self {
+            x: final_acc_x,
+            y: final_acc_y,
+            is_infinity: Boolean::constant(false),
+            value: final_value
+        };
+        let result = result.sub_unequal(cs, &mut offset)?;
+
+        Ok(result)
+    }
+
+    // the same ladder with the accumulator in projective coordinates: there are no inversions
+    // during the accumulation, the only ones are spent on the final conversion into affine form
+    #[track_caller]
+    fn skewed_ladder_with_projective_accumulator<CS: ConstraintSystem<E>>(
+        &mut self, cs: &mut CS, digits: &[Boolean], offset_generator: G
+    ) -> Result<Self, SynthesisError> {
+        assert!(digits.len() > 2, "skewed decomposition should contain at least the first and the last entries");
+        assert!(!offset_generator.is_zero(), "offset generator can not be the point at infinity");
+        let params = self.x.representation_params;
+
+        let generator = Self::constant(offset_generator, params);
+        let mut acc = ProjectivePoint::from(generator).add_mixed(cs, self)?;
+
+        let entries_without_first_and_last = &digits[1..(digits.len() - 1)];
+        let mut num_doubles = 0;
+
+        let mut minus_y = self.y.negate(cs)?;
+        minus_y.prepare_for_select(cs)?;
+
+        for e in entries_without_first_and_last.iter() {
+            let selected_y = FieldElement::conditionally_select(cs, e, &minus_y, &self.y)?;  
+            let t_value = match (self.value, e.get_value()) {
+                (Some(val), Some(bit)) => {
+                    let mut val = val;
+                    if bit {
+                        val.negate();
+                    }
+                    Some(val)
+                },
+                _ => None
+            };
+            let t = Self {
+                x: self.x.clone(),
+                y: selected_y,
+                is_infinity: Boolean::constant(false),
+                value: t_value
+            };
+
+            acc = acc.double(cs)?;
+            acc = acc.add_mixed(cs, &t)?;
+            num_doubles += 1;
+        }
+
+        let minus_self = self.negate(cs)?;
+        let with_skew = acc.add_mixed(cs, &minus_self)?;
+        let last_entry = digits.last().unwrap();
+        let acc = ProjectivePoint::conditionally_select(cs, last_entry, &with_skew, &acc)?;
+
+        let mut scaled_offset = offset_generator.into_projective();
+        for _ in 0..num_doubles {
+            scaled_offset.double();
+        }
+        let mut minus_scaled_offset = scaled_offset.into_affine();
+        minus_scaled_offset.negate();
+        let minus_offset = Self::constant(minus_scaled_offset, params);
+        let mut result = acc.add_mixed(cs, &minus_offset)?;
+
+        // the result is at infinity if the order of P divides the scalar, so z = 0 should be handled
+        let (result, _) = result.convert_to_affine_or_default(cs, &Self::zero(params))?;
+        Ok(result)
+    }
+}
+
+
+impl<'a, E: Engine, G: GenericCurveAffine> AffinePoint<'a, E, G> where <G as GenericCurveAffine>::Base: PrimeField {
+    pub fn mul_by_scalar_for_prime_order_curve<CS: ConstraintSystem<E>>(
+        &mut self, cs: &mut CS, scalar: &mut FieldElement<'a, E, G::Scalar>
+    ) -> Result<ProjectivePoint<'a, E, G>, SynthesisError> {
+        let params = self.x.representation_params;
+        if scalar.is_constant() {
+            let value = scalar.get_field_value().unwrap();
+            if value.is_zero() {
+                return Ok(ProjectivePoint::<E, G>::zero(params));
+            }
+            if value == G::Scalar::one() {
+                return ProjectivePoint::from_affine(cs, self);
+            }
+        }
+        // mixed addition ignores the infinity flag of the affine operand, so the loop works with the regular point: 
+        // for the curve of odd order [2^i]P is at infinity iff P is, and the flag is applied to the result afterwards
+        let mut tmp = match self.is_infinity {
+            Boolean::Constant(true) => return Ok(ProjectivePoint::<E, G>::zero(params)),
+            Boolean::Constant(false) => self.clone(),
+            _ => {
+                let generator = Self::constant(G::one(), params);
+                let mut tmp = Self::select(cs, &self.is_infinity, &generator, self)?;
+                tmp.is_infinity = Boolean::constant(false);
+                tmp
+            }
+        };
+        let scalar_decomposition = scalar.decompose_into_binary_representation(cs)?;
+
+        // TODO: use standard double-add algorithm for now, optimize later
+        let mut acc = ProjectivePoint::<E, G>::zero(params);
+
+        for bit in scalar_decomposition.into_iter() {
+            let added = acc.add_mixed(cs, &mut tmp)?;
+            acc = ProjectivePoint::conditionally_select(cs, &bit, &added, &acc)?;
+            tmp = tmp.double(cs)?;
+        }
+        
+        if self.is_infinity.is_constant() {
+            Ok(acc)
+        } else {
+            let zero = ProjectivePoint::<E, G>::zero(params);
+            ProjectivePoint::conditionally_select(cs, &self.is_infinity, &zero, &acc)
+        }
+    }
+
+    pub fn mul_by_scalar_for_prime_order_curve_with_strategy<CS: ConstraintSystem<E>>(
+        &mut self, cs: &mut CS, scalar: &mut FieldElement<'a, E, G::Scalar>, strategy: PointByScalarMulStrategy
+    ) -> Result<ProjectivePoint<'a, E, G>, SynthesisError> {
+        match strategy {
+            PointByScalarMulStrategy::Basic => self.mul_by_scalar_for_prime_order_curve(cs, scalar),
+            PointByScalarMulStrategy::FixedWindow(window) => self.mul_by_scalar_with_fixed_window(cs, scalar, window),
+            PointByScalarMulStrategy::MontgomeryLadder => {
+                let res = self.mul_by_scalar_montgomery(cs, scalar)?;
+                ProjectivePoint::from_affine(cs, &res)
+            },
+        }
+    }
+
+    // Montgomery ladder: bits are processed starting from the most significant one with R0 = O and R1 = P,
+    // for every bit b we set (R0, R1) = b ? (R0 + R1, 2 * R1) : (2 * R0, R0 + R1), so that R1 - R0 = P holds 
+    // throughout and the result is R0. The cost of all the iterations is the same (one ladder addition, one
+    // doubling and three selections), leading zero bits (and zero scalar) are handled via the infinity flag.
+    // The invariant lets the ladder addition skip the tangent branch of add_complete together with both 
+    // equality checks, which makes the ladder cheaper than the Basic strategy (one mixed projective addition 
+    // and one affine doubling per bit)
+    #[track_caller]
+    pub fn mul_by_scalar_montgomery<CS: ConstraintSystem<E>>(
+        &mut self, cs: &mut CS, scalar: &mut FieldElement<'a, E, G::Scalar>
+    ) -> Result<Self, SynthesisError> {
+        let params = self.x.representation_params;
+        if scalar.is_constant() {
+            let value = scalar.get_field_value().unwrap();
+            if value.is_zero() {
+                return Ok(Self::zero(params));
+            }
+            if value == G::Scalar::one() {
+                return Ok(self.clone());
+            }
+        }
+
+        // the ladder addition relies on P being regular: the point at infinity is replaced by the generator 
+        // and the result is switched back to O afterwards
+        let point = match self.is_infinity {
+            Boolean::Constant(true) => return Ok(Self::zero(params)),
+            Boolean::Constant(false) => self.clone(),
+            _ => {
+                let generator = Self::constant(G::one(), params);
+                let mut point = Self::select(cs, &self.is_infinity, &generator, self)?;
+                point.is_infinity = Boolean::constant(false);
+                point
+            }
+        };
+        let scalar_decomposition = scalar.decompose_into_binary_representation(cs)?;
+
+        let mut r0 = Self::zero(params);
+        let mut r1 = point;
+        for bit in scalar_decomposition.iter().rev() {
+            let sum = Self::ladder_add(cs, &r0, &r1)?;
+            let to_double = Self::select(cs, bit, &r1, &r0)?;
+            let doubled = to_double.double(cs)?;
+            r0 = Self::select(cs, bit, &sum, &doubled)?;
+            r1 = Self::select(cs, bit, &doubled, &sum)?;
+        }
+
+        if self.is_infinity.is_constant() {
+            Ok(r0)
+        } else {
+            Self::select(cs, &self.is_infinity, &Self::zero(params), &r0)
+        }
+    }
+
+    // addition of the ladder registers R0 and R1 = R0 + P for regular P: as R0 != R1 the x coordinates coincide 
+    // only if one of the registers is at infinity (its coordinates are meaningless) or R1 = -R0 (the sum is O), 
+    // so the chord formula with the denominator replaced by one in these cases is all we need
+    fn ladder_add<CS: ConstraintSystem<E>>(cs: &mut CS, r0: &Self, r1: &Self) -> Result<Self, SynthesisError> {
+        let params = r0.x.representation_params;
+        let chord_num = r1.y.sub(cs, &r0.y)?;
+        let mut chord_den = r1.x.sub(cs, &r0.x)?;
+        let den_is_zero = chord_den.is_zero(cs)?;
+        let safe_den = FieldElement::conditionally_select(cs, &den_is_zero, &FieldElement::one(params), &chord_den)?;
+        let lambda = chord_num.div(cs, &safe_den)?;
+
+        // lambda^2 + (-x1 - x0)
+        let mut chain = FieldElementsChain::new();
+        chain.add_neg_term(&r1.x).add_neg_term(&r0.x);
+        let new_x = lambda.square_with_chain(cs, chain)?;
+
+        // lambda * (x0 - new_x) + (- y0)
+        let x0_minus_new_x = r0.x.sub(cs, &new_x)?;
+        let mut chain = FieldElementsChain::new();
+        chain.add_neg_term(&r0.y);
+        let new_y = FieldElement::mul_with_chain(cs, &lambda, &x0_minus_new_x, chain)?;
+
+        // O + R1 = R1, R0 + O = R0 (the registers are never both at infinity)
+        let x = FieldElement::conditionally_select(cs, &r1.is_infinity, &r0.x, &new_x)?;
+        let x = FieldElement::conditionally_select(cs, &r0.is_infinity, &r1.x, &x)?;
+        let y = FieldElement::conditionally_select(cs, &r1.is_infinity, &r0.y, &new_y)?;
+        let y = FieldElement::conditionally_select(cs, &r0.is_infinity, &r1.y, &y)?;
+        let is_infinity = Boolean::conditionally_select(cs, &r1.is_infinity, &r0.is_infinity, &den_is_zero)?;
+        let is_infinity = Boolean::conditionally_select(cs, &r0.is_infinity, &r1.is_infinity, &is_infinity)?;
+
+        let new_value = match (r0.value, r1.value) {
+            (Some(this), Some(other)) => {
+                let mut tmp = this.into_projective();
+                tmp.add_assign_mixed(&other);
+                Some(tmp.into_affine())
+            },
+            _ => None
+        };
+
+        let mut new = Self {
+            x,
+            y,
+            is_infinity,
+            value: new_value
+        };
+        new.apply_output_reduction_policy(cs)?;
+        new.debug_check_value_consistency();
+        Ok(new)
+    }
+
+    // the precomputed table is affine: [P, 2P, ..., (2^w - 1)P] is obtained via double and add_unequal 
+    // (all the multiples are distinct as the order of the group is much larger than 2^w), and the multiple corresponding
+    // to the window is chosen by the binary tree of selects. The accumulator is projective: the complete formulas 
+    // take care of the leading zero windows, while the zero window itself is handled by an explicit flag
+    fn mul_by_scalar_with_fixed_window<CS: ConstraintSystem<E>>(
+        &mut self, cs: &mut CS, scalar: &mut FieldElement<'a, E, G::Scalar>, window: usize
+    ) -> Result<ProjectivePoint<'a, E, G>, SynthesisError> {
+        let scalar_decomposition = scalar.decompose_into_binary_representation(cs)?;
+        self.mul_by_bits_with_fixed_window(cs, &scalar_decomposition, window)
+    }
+
+    // multiplication by the scalar which is known to fit into max_bits bits: the high bits of the decomposition
+    // are enforced to be zero, and the windows they form are skipped altogether (no doublings, selections
+    // and additions). NB: max_bits defines the shape of the circuit, so it should be fixed at setup time rather 
+    // than derived from the witness, otherwise the circuit won't match the verification key
+    pub fn mul_by_small_scalar_with_fixed_window<CS: ConstraintSystem<E>>(
+        &mut self, cs: &mut CS, scalar: &mut FieldElement<'a, E, G::Scalar>, window: usize, max_bits: usize
+    ) -> Result<ProjectivePoint<'a, E, G>, SynthesisError> {
+        assert!(max_bits > 0);
+        let scalar_decomposition = scalar.decompose_into_binary_representation(cs)?;
+        if max_bits >= scalar_decomposition.len() {
+            return self.mul_by_bits_with_fixed_window(cs, &scalar_decomposition, window);
+        }
+        // we don't go through Boolean::enforce_equal, which panics on unequal witnesses
+        for bit in scalar_decomposition[max_bits..].iter() {
+            match bit {
+                Boolean::Constant(flag) => if *flag { return Err(SynthesisError::Unsatisfiable) },
+                _ => bit.lc(E::Fr::one()).enforce_zero(cs)?,
+            }
+        }
+        self.mul_by_bits_with_fixed_window(cs, &scalar_decomposition[..max_bits], window)
+    }
+
+    // bits are in little-endian order
+    fn mul_by_bits_with_fixed_window<CS: ConstraintSystem<E>>(
+        &mut self, cs: &mut CS, scalar_decomposition: &[Boolean], window: usize
+    ) -> Result<ProjectivePoint<'a, E, G>, SynthesisError> {
+        assert!(window > 0 && window <= MAX_WINDOW_SIZE);
+        let params = self.x.representation_params;
+
+        let table_size = 1 << window;
+        // the entry for zero window is never used, so we just put the point itself there
+        let mut table = Vec::with_capacity(table_size);
+        table.push(self.clone());
+        table.push(self.clone());
+        if table_size > 2 {
+            table.push(self.double(cs)?);
+        }
+        for j in 3..table_size {
+            let next = table[j - 1].add_unequal(cs, self)?;
+            table.push(next);
+        }
+
+        let mut acc = ProjectivePoint::<E, G>::zero(params);
+        for (is_first, _is_last, window_bits) in scalar_decomposition.chunks(window).rev().identify_first_last() {
+            // only the most significant window may be shorter, it is processed first 
+            if !is_first {
+                for _ in 0..window {
+                    acc = acc.double(cs)?;
+                }
+            }
+
+            let selected = Self::select_from_table(cs, window_bits, &table[..(1 << window_bits.len())])?;
+
+            let mut window_is_zero = Boolean::constant(true);
+            for bit in window_bits.iter() {
+                window_is_zero = Boolean::and(cs, &window_is_zero, &bit.not())?;
+            }
+            let added = acc.add_mixed(cs, &selected)?;
+            acc = ProjectivePoint::conditionally_select(cs, &window_is_zero, &acc, &added)?;
+        }
+
+        Ok(acc)
+    }
+
+    // cost model of mul_by_bits_with_fixed_window (in gates), following its operation counts: multiplication of 
+    // field elements with n binary limbs is estimated as (n+1)^2 gates, selection of a coordinate as n+1 gates.
+    // The affine table takes one doubling (3 multiplications, division included) and 2^w - 3 chord additions
+    // (4 multiplications with the inequality check). Every window costs 2^w - 1 selections of both coordinates, 
+    // the mixed projective addition (13 multiplications, the ones by 3b included), the zero window flag and 
+    // the selection of the projective accumulator, while all but the first one require w projective doublings 
+    // (9 multiplications each). See test_windowed_mul_with_auto_window for the comparison with measured counts
+    pub fn estimate_windowed_mul_cost(num_bits: usize, window: usize, num_limbs: usize) -> usize {
+        assert!(window > 0 && window <= MAX_WINDOW_SIZE);
+        let mul_cost = (num_limbs + 1) * (num_limbs + 1);
+        let select_cost = num_limbs + 1;
+        let add_mixed_cost = 13 * mul_cost;
+        let double_cost = 9 * mul_cost;
+
+        let table_size = 1 << window;
+        let num_windows = (num_bits + window - 1) / window;
+        let table_cost = if table_size > 2 { 3 * mul_cost + (table_size - 3) * 4 * mul_cost } else { 0 };
+        let selection_cost = num_windows * (table_size - 1) * 2 * select_cost;
+        let accumulation_cost = num_windows * (add_mixed_cost + 3 * select_cost + window - 1) + 
+            (num_windows - 1) * window * double_cost;
+
+        table_cost + selection_cost + accumulation_cost
+    }
+
+    pub fn optimal_window_size(num_bits: usize, num_limbs: usize) -> usize {
+        (1..=MAX_WINDOW_SIZE).min_by_key(|window| Self::estimate_windowed_mul_cost(num_bits, *window, num_limbs)).unwrap()
+    }
+
+    // windowed multiplication by the scalar which fits into num_bits bits (G::Scalar::NUM_BITS for arbitrary scalar)
+    // with the window chosen by the cost model above: as for mul_by_small_scalar_with_fixed_window the high bits 
+    // are enforced to be zero and num_bits should be fixed at setup time
+    pub fn windowed_mul<CS: ConstraintSystem<E>>(
+        &mut self, cs: &mut CS, scalar: &mut FieldElement<'a, E, G::Scalar>, num_bits: usize
+    ) -> Result<ProjectivePoint<'a, E, G>, SynthesisError> {
+        assert!(num_bits > 0 && num_bits <= G::Scalar::NUM_BITS as usize);
+        let num_limbs = self.x.representation_params.get_num_binary_limbs();
+        let window = Self::optimal_window_size(num_bits, num_limbs);
+        self.mul_by_small_scalar_with_fixed_window(cs, scalar, window, num_bits)
+    }
+
+    // the same as the FixedWindow strategy of mul_by_scalar_for_prime_order_curve_with_strategy
+    pub fn windowed_mul_with_window<CS: ConstraintSystem<E>>(
+        &mut self, cs: &mut CS, scalar: &mut FieldElement<'a, E, G::Scalar>, window: usize
+    ) -> Result<ProjectivePoint<'a, E, G>, SynthesisError> {
+        self.mul_by_scalar_with_fixed_window(cs, scalar, window)
+    }
+
+    // computes u1 * fixed_base + u2 * q (e.g. in ECDSA verification):
+    // the fixed base part doesn't require any doublings - we precompute the table of constant points
+    // [j * 4^i] * fixed_base for j in {0, 1, 2, 3} and consume the scalar by windows of two bits; 
+    // selection between constant points on the first level is free, as well as the identity (0: 1: 0) is
+    // representable in projective coordinates, so we use complete addition for the accumulation
+    // the variable base part is computed by the regular double-and-add and both parts share the final addition
+    pub fn fixed_and_variable_mul<CS: ConstraintSystem<E>>(
+        cs: &mut CS, fixed_base: G, u1: &mut FieldElement<'a, E, G::Scalar>, 
+        q: &mut Self, u2: &mut FieldElement<'a, E, G::Scalar>
+    ) -> Result<ProjectivePoint<'a, E, G>, SynthesisError> {
+        let params = q.x.representation_params;
+        let u1_decomposition = u1.decompose_into_binary_representation(cs)?;
+
+        let mut acc = ProjectivePoint::<E, G>::zero(params);
+        let mut base = fixed_base.into_projective();
+        for window in u1_decomposition.chunks(2) {
+            let mut table = Vec::with_capacity(4);
+            table.push(ProjectivePoint::<E, G>::zero(params));
+            let mut multiple = base;
+            for _ in 1..4 {
+                table.push(ProjectivePoint::from(AffinePoint::constant(multiple.into_affine(), params)));
+                multiple.add_assign(&base);
+            }
+            // here multiple = 4 * base
+            base = multiple;
+
+            let low_bit = window[0];
+            let high_bit = window.get(1).cloned().unwrap_or(Boolean::constant(false));
+            let low = ProjectivePoint::conditionally_select(cs, &low_bit, &table[1], &table[0])?;
+            let high = ProjectivePoint::conditionally_select(cs, &low_bit, &table[3], &table[2])?;
+            let selected = ProjectivePoint::conditionally_select(cs, &high_bit, &high, &low)?;
+            acc = acc.add(cs, &selected)?;
+        }
+
+        let variable_part = q.mul_by_scalar_for_prime_order_curve(cs, u2)?;
+        acc.add(cs, &variable_part)
+    }
+
+    // sum of [scalars[i]] * points[i] (Straus-Shamir): the bits of all the scalars are scanned jointly
+    // starting from the most significant one, so there is a single doubling of the accumulator per bit
+    // position for the whole batch, and the points are added to the projective accumulator in mixed form.
+    // The accumulator starts at the projective zero, so there is no offset generator to correct for at all,
+    // and zero scalars are handled by the complete projective formulas (constant zero terms are skipped)
+    pub fn multiexp<CS: ConstraintSystem<E>>(
+        cs: &mut CS, scalars: &mut [FieldElement<'a, E, G::Scalar>], points: &mut [Self]
+    ) -> Result<ProjectivePoint<'a, E, G>, SynthesisError> {
+        if scalars.len() != points.len() || points.is_empty() {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        let params = points[0].x.representation_params;
+
+        let mut terms = Vec::with_capacity(scalars.len());
+        for (scalar, point) in scalars.iter_mut().zip(points.iter()) {
+            if scalar.is_constant() && scalar.get_field_value().unwrap().is_zero() {
+                continue;
+            }
+            terms.push((point, scalar.decompose_into_binary_representation(cs)?));
+        }
+        ProjectivePoint::joint_bit_scan(cs, &terms, params, |cs, acc, point| acc.add_mixed(cs, point))
+    }
+
+    // sum of [scalar] * point over the included terms only: excluded terms contribute the identity,
+    // which is achieved by masking every bit of the scalar with the inclusion flag, so that the joint
+    // bit scan of multiexp is shared by all the terms regardless of the flags
+    pub fn masked_multiexp<CS: ConstraintSystem<E>>(
+        cs: &mut CS, terms: &mut [(Self, FieldElement<'a, E, G::Scalar>, Boolean)]
+    ) -> Result<AffinePointWithInfinity<'a, E, G>, SynthesisError> {
+        if terms.is_empty() {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        let params = terms[0].0.x.representation_params;
+
+        let mut masked_terms = Vec::with_capacity(terms.len());
+        for (point, scalar, include) in terms.iter_mut() {
+            if let Boolean::Constant(false) = include {
+                continue;
+            }
+            let mut masked_bits = vec![];
+            for bit in scalar.decompose_into_binary_representation(cs)?.into_iter() {
+                masked_bits.push(Boolean::and(cs, &bit, include)?);
+            }
+            masked_terms.push((&*point, masked_bits));
+        }
+        let mut sum = ProjectivePoint::joint_bit_scan(
+            cs, &masked_terms, params, |cs, acc, point| acc.add_mixed(cs, point)
+        )?;
+        let (res, _) = sum.convert_to_affine_or_default(cs, &Self::zero(params))?;
+
+        Ok(res)
+    }
+
+    // [m]P for the integer m known at synthesis time: the NAF digits of m are constant, so we only emit
+    // the doublings and the (on average |m| / 3) additions of +/- P that are actually needed. 
+    // Intermediate results may hit the point at infinity (e.g. if the order of P divides a prefix of m), 
+    // hence the complete addition law
+    #[track_caller]
+    pub(crate) fn mul_by_biguint_constant<CS: ConstraintSystem<E>>(
+        &mut self, cs: &mut CS, m: &BigUint
+    ) -> Result<Self, SynthesisError> {
+        self.mul_by_biguint_constant_with_doubling(cs, m, None)
+    }
+
+    // precomputed 2P (if any) replaces the first doubling of the scan
+    fn mul_by_biguint_constant_with_doubling<CS: ConstraintSystem<E>>(
+        &mut self, cs: &mut CS, m: &BigUint, mut doubled: Option<Self>
+    ) -> Result<Self, SynthesisError> {
+        let params = self.x.representation_params;
+        let digits = compute_naf(m);
+        if digits.is_empty() {
+            return Ok(Self::zero(params));
+        }
+        let mut minus_self = self.negate(cs)?;
+
+        // the most significant NAF digit is always 1
+        let mut acc = self.clone();
+        for digit in digits.iter().rev().skip(1) {
+            acc = match doubled.take() {
+                Some(doubled) => doubled,
+                None => acc.double(cs)?,
+            };
+            match *digit {
+                1 => acc = acc.add_complete(cs, self)?,
+                -1 => acc = acc.add_complete(cs, &mut minus_self)?,
+                _ => {},
+            }
+        }
+
+        Ok(acc)
+    }
+
+    // enforces [m]P = O, i.e. that the order of P divides m: for m equal to the group order (resp. to
+    // the order of the prime subgroup) this is a subgroup check, and for m equal to the cofactor it checks
+    // that P lies in the small torsion subgroup
+    // NB: we don't go through Boolean::enforce_equal, which panics on unequal witnesses: 
+    // the point of the wrong order should lead to an unsatisfied circuit rather than abort the synthesis
+    #[track_caller]
+    pub fn enforce_order_divides<CS: ConstraintSystem<E>>(&mut self, cs: &mut CS, m: &BigUint) -> Result<(), SynthesisError> {
+        self.enforce_order_divides_with_doubling(cs, m, None)
+    }
+
+    fn enforce_order_divides_with_doubling<CS: ConstraintSystem<E>>(
+        &mut self, cs: &mut CS, m: &BigUint, doubled: Option<Self>
+    ) -> Result<(), SynthesisError> {
+        let res = self.mul_by_biguint_constant_with_doubling(cs, m, doubled)?;
+        match res.is_infinity {
+            Boolean::Constant(flag) => if flag { Ok(()) } else { Err(SynthesisError::Unsatisfiable) },
+            ref flag => {
+                let mut minus_one = E::Fr::one();
+                minus_one.negate();
+                let mut lc = flag.lc(E::Fr::one());
+                lc.add_assign_constant(minus_one);
+                lc.enforce_zero(cs)
+            }
+        }
+    }
+
+    // enforces [r]P = O, where r is the order of the scalar field: for prime order curves every point of the curve 
+    // is in the subgroup and nothing should be checked
+    #[track_caller]
+    pub fn enforce_if_in_subgroup<CS: ConstraintSystem<E>>(
+        &mut self, cs: &mut CS, curve_params: &CurveCircuitParameters<E, G>
+    ) -> Result<(), SynthesisError> {
+        if curve_params.is_prime_order_curve {
+            return Ok(());
+        }
+        let subgroup_order = repr_to_biguint::<G::Scalar>(&G::Scalar::char());
+        self.enforce_order_divides(cs, &subgroup_order)
+    }
+
+    // predicate counterpart of enforce_if_in_subgroup: [r]P == O
+    #[track_caller]
+    pub fn is_in_subgroup<CS: ConstraintSystem<E>>(
+        &mut self, cs: &mut CS, curve_params: &CurveCircuitParameters<E, G>
+    ) -> Result<Boolean, SynthesisError> {
+        if curve_params.is_prime_order_curve {
+            return Ok(Boolean::constant(true));
+        }
+        let subgroup_order = repr_to_biguint::<G::Scalar>(&G::Scalar::char());
+        let res = self.mul_by_biguint_constant(cs, &subgroup_order)?;
+        Ok(res.is_infinity)
+    }
+
+    // y^2 == x^3 + a * x + b
+    pub fn is_on_curve<CS: ConstraintSystem<E>>(&self, cs: &mut CS) -> Result<Boolean, SynthesisError> {
+        let params = self.x.representation_params;
+        let b = FieldElement::constant(G::b_coeff(), params);
+        let mut lhs = self.y.square(cs)?;
+        let x_squared = self.x.square(cs)?;
+        let x_cubed = x_squared.mul(cs, &self.x)?;
+        let mut rhs = x_cubed.add(cs, &b)?;
+        if !G::a_coeff().is_zero() {
+            let a = FieldElement::constant(G::a_coeff(), params);
+            let a_x = a.mul(cs, &self.x)?;
+            rhs = rhs.add(cs, &a_x)?;
+        }
+        FieldElement::equals(cs, &mut lhs, &mut rhs)
+    }
+
+    // validity flag of the (regular) point coming from untrusted source: nothing is enforced, so the verifier
+    // may aggregate the flags of several points and decide on its own how to react on the invalid ones
+    #[track_caller]
+    pub fn is_valid_point<CS: ConstraintSystem<E>>(
+        &mut self, cs: &mut CS, curve_params: &CurveCircuitParameters<E, G>
+    ) -> Result<Boolean, SynthesisError> {
+        let is_on_curve = self.is_on_curve(cs)?;
+        let is_in_subgroup = self.is_in_subgroup(cs, curve_params)?;
+        Boolean::and(cs, &is_on_curve, &is_in_subgroup)
+    }
+
+    // enforces both the curve equation and [r]P = O for the regular point: x^2 is computed once and shared by the 
+    // curve equation y^2 = x^2 * x + a * x + b (checked via the single chained multiplication) and the first 
+    // doubling of the subgroup check
+    #[track_caller]
+    pub fn enforce_valid<CS: ConstraintSystem<E>>(
+        &mut self, cs: &mut CS, curve_params: &CurveCircuitParameters<E, G>
+    ) -> Result<(), SynthesisError> {
+        match self.is_infinity {
+            Boolean::Constant(false) => {},
+            _ => return Err(SynthesisError::Unsatisfiable),
+        }
+        let params = self.x.representation_params;
+        let b = FieldElement::constant(G::b_coeff(), params);
+        let y_squared = self.y.square(cs)?;
+        let x_squared = self.x.square(cs)?;
+        let mut chain = FieldElementsChain::new();
+        chain.add_pos_term(&b).add_neg_term(&y_squared);
+        if !G::a_coeff().is_zero() {
+            let a = FieldElement::constant(G::a_coeff(), params);
+            let a_x = a.mul(cs, &self.x)?;
+            chain.add_pos_term(&a_x);
+        }
+        let mut residual = FieldElement::mul_with_chain(cs, &x_squared, &self.x, chain)?;
+        residual.enforce_equal_to_constant(cs, G::Base::zero())?;
+
+        if curve_params.is_prime_order_curve {
+            return Ok(());
+        }
+        let doubled = self.double_with_x_squared(cs, &x_squared)?;
+        let subgroup_order = repr_to_biguint::<G::Scalar>(&G::Scalar::char());
+        self.enforce_order_divides_with_doubling(cs, &subgroup_order, Some(doubled))
+    }
+}
+
+
+impl<'a, E: Engine, G: CurveWithEndomorphism> AffinePoint<'a, E, G> where <G as GenericCurveAffine>::Base: PrimeField {
+    // [lambda]P at the cost of a single multiplication by constant and a negation
+    pub fn apply_endomorphism<CS: ConstraintSystem<E>>(&self, cs: &mut CS) -> Result<Self, SynthesisError> {
+        let params = self.x.representation_params;
+        let beta = FieldElement::constant(G::endomorphism_beta(), params);
+        let x = self.x.mul(cs, &beta)?;
+        let y = self.y.negate(cs)?;
+
+        let value = self.value.map(|pt| {
+            if pt.is_zero() {
+                return pt;
+            }
+            let (mut x, mut y) = pt.into_xy_unchecked();
+            x.mul_assign(&G::endomorphism_beta());
+            y.negate();
+            G::from_xy_unchecked(x, y)
+        });
+
+        let new = Self { x, y, is_infinity: self.is_infinity.clone(), value };
+        new.debug_check_value_consistency();
+        Ok(new)
+    }
+}
+
+
+impl<'a, E: Engine, G: CurveWithCofactor> AffinePoint<'a, E, G> where <G as GenericCurveAffine>::Base: PrimeField {
+    // maps an arbitrary point on the curve into the prime order subgroup: the result is [h]P, 
+    // which is the point at infinity iff P lies in the small torsion subgroup
+    #[track_caller]
+    pub fn clear_cofactor<CS: ConstraintSystem<E>>(&mut self, cs: &mut CS) -> Result<AffinePointWithInfinity<'a, E, G>, SynthesisError> {
+        self.mul_by_biguint_constant(cs, &G::cofactor())
+    }
+}
+
+impl<'a, E: Engine, G: GenericCurveAffine> AffinePoint<'a, E, G> where <G as GenericCurveAffine>::Base: PrimeField {
+    // checks the curve equations of all the points. Combining the equations by a random challenge doesn't pay off
+    // for non-native arithmetic: every term of the combination still needs y_i^2, x_i^2 and x_i^3, while the
+    // challenge costs absorption of all the coordinates and N - 1 multiplications by r. Instead the residual 
+    // t_i = x_i^3 + a * x_i + b - y_i^2 is produced by a single mul_with_chain and enforced to be zero: unlike
+    // N separate calls of enforce_curve_equation, neither x^3 + b is materialized nor the sides are reduced
+    // for the comparison (see test_enforce_batch_on_curve).
+    // All the points should be statically known to be regular (the flag is constant false), 
+    // otherwise Unsatisfiable error is returned: point at infinity has no curve equation
+    #[track_caller]
+    pub fn enforce_batch_on_curve<CS: ConstraintSystem<E>>(cs: &mut CS, points: &[Self]) -> Result<(), SynthesisError> {
+        for point in points.iter() {
+            match point.is_infinity {
+                Boolean::Constant(false) => {},
+                _ => return Err(SynthesisError::Unsatisfiable),
+            }
+        }
+        if points.is_empty() {
+            return Ok(());
+        }
+        let params = points[0].x.representation_params;
+
+        let b = FieldElement::constant(G::b_coeff(), params);
+        let a = FieldElement::constant(G::a_coeff(), params);
+        for point in points.iter() {
+            let y_squared = point.y.square(cs)?;
+            let x_squared = point.x.square(cs)?;
+            let mut chain = FieldElementsChain::new();
+            chain.add_pos_term(&b).add_neg_term(&y_squared);
+            if !G::a_coeff().is_zero() {
+                let a_x = a.mul(cs, &point.x)?;
+                chain.add_pos_term(&a_x);
+            }
+            let mut residual = FieldElement::mul_with_chain(cs, &x_squared, &point.x, chain)?;
+            residual.enforce_equal_to_constant(cs, G::Base::zero())?;
+        }
+
+        Ok(())
+    }
+}
+
+// test support: points allocated under different RNS parameters have different limb representations,
+// so they can not be compared in the circuit, but their witnesses still should coincide
+#[track_caller]
+pub fn assert_same_point_value<'a, 'b, E: Engine, G: GenericCurveAffine>(
+    a: &AffinePoint<'a, E, G>, b: &AffinePoint<'b, E, G>
+) where <G as GenericCurveAffine>::Base: PrimeField {
+    let a_value = a.get_value().expect("witness of the first point is unknown");
+    let b_value = b.get_value().expect("witness of the second point is unknown");
+    assert!(a_value == b_value, "points have different values: {} and {}", a_value, b_value);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::bellman::pairing::bn256::{Fq, Bn256, Fr, G1Affine};
+    use plonk::circuit::Width4WithCustomGates;
+    use bellman::plonk::better_better_cs::gates::{selector_optimized_with_d_next::SelectorOptimizedWidth4MainGateWithDNext, self};
+    use rand::{XorShiftRng, SeedableRng, Rng};
+    use bellman::plonk::better_better_cs::cs::*;
+
+    #[test]
+    fn test_arithmetic_for_bn256_curve() {
+        let mut cs = TrivialAssembly::<Bn256, Width4WithCustomGates, SelectorOptimizedWidth4MainGateWithDNext>::new();
+        inscribe_default_bitop_range_table(&mut cs).unwrap();
+        let params = RnsParameters::<Bn256, Fq>::new_optimal(&mut cs, 80usize);
+        let scalar_params = RnsParameters::<Bn256, Fr>::new_optimal(&mut cs, 80usize);
+        let mut rng = rand::thread_rng();
+
+        let a: G1Affine = rng.gen();
+        let scalar : Fr = rng.gen();
+        let mut tmp = a.into_projective();
+        tmp.mul_assign(scalar);
+        let result = tmp.into_affine();
+        
+        let mut a = AffinePoint::alloc(&mut cs, Some(a), &params).unwrap();
+        let mut scalar = FieldElement::alloc(&mut cs, Some(scalar), &scalar_params).unwrap();
+        let mut actual_result = AffinePoint::alloc(&mut cs, Some(result), &params).unwrap();
+        let naive_mul_start = cs.get_current_step_number();
+        let mut result = a.mul_by_scalar_for_prime_order_curve(&mut cs, &mut scalar).unwrap();
+        let mut result = unsafe { result.convert_to_affine(&mut cs).unwrap() };
+        let naive_mul_end = cs.get_current_step_number();
+        println!("num of gates: {}", naive_mul_end - naive_mul_start);
+
+        // println!("actual result: x: {}, y: {}", actual_result.x.get_field_value().unwrap(), actual_result.y.get_field_value().unwrap());
+        // println!("computed result: x: {}, y: {}", result.x.get_field_value().unwrap(), result.y.get_field_value().unwrap());
+
+        AffinePoint::enforce_equal(&mut cs, &mut result, &mut actual_result).unwrap();
+        assert!(cs.is_satisfied()); 
+        println!("SCALAR MULTIPLICATION final");
+    }
+
+    #[test]
+    fn test_arithmetic_for_secp256k1_curve() {
+        use super::super::secp256k1::fq::Fq as SecpFq;
+        use super::super::secp256k1::fr::Fr as SecpFr;
+        use super::super::secp256k1::PointAffine as SecpG1;
+
+        let mut cs = TrivialAssembly::<Bn256, Width4WithCustomGates, SelectorOptimizedWidth4MainGateWithDNext>::new();
+        inscribe_default_bitop_range_table(&mut cs).unwrap();
+        let params = RnsParameters::<Bn256, SecpFq>::new_optimal(&mut cs, 64usize);
+        let scalar_params = RnsParameters::<Bn256, SecpFr>::new_optimal(&mut cs, 80usize);
+        let mut rng = rand::thread_rng();
+
+        let a: SecpG1 = rng.gen();
+        let scalar : SecpFr = rng.gen();
+        let mut tmp = a.into_projective();
+        tmp.mul_assign(scalar);
+        let result = tmp.into_affine();
+        
+        let mut a = AffinePoint::alloc(&mut cs, Some(a), &params).unwrap();
+        let mut scalar = FieldElement::alloc(&mut cs, Some(scalar), &scalar_params).unwrap();
+        let mut actual_result = AffinePoint::alloc(&mut cs, Some(result), &params).unwrap();
+        let naive_mul_start = cs.get_current_step_number();
+        let mut result = a.mul_by_scalar_for_prime_order_curve(&mut cs, &mut scalar).unwrap();
+        let mut result = unsafe { result.convert_to_affine(&mut cs).unwrap() };
+        let naive_mul_end = cs.get_current_step_number();
+        println!("num of gates: {}", naive_mul_end - naive_mul_start);
+
+        // println!("actual result: x: {}, y: {}", actual_result.x.get_field_value().unwrap(), actual_result.y.get_field_value().unwrap());
+        // println!("computed result: x: {}, y: {}", result.x.get_field_value().unwrap(), result.y.get_field_value().unwrap());
+
+        AffinePoint::enforce_equal(&mut cs, &mut result, &mut actual_result).unwrap();
+        assert!(cs.is_satisfied()); 
+        println!("SCALAR MULTIPLICATION final");
+    }
+
+    #[test]
+    fn test_mul_by_skewed_digits() {
+        let mut cs = TrivialAssembly::<Bn256, Width4WithCustomGates, SelectorOptimizedWidth4MainGateWithDNext>::new();
+        inscribe_default_bitop_range_table(&mut cs).unwrap();
+        let params = RnsParameters::<Bn256, Fq>::new_optimal(&mut cs, 80usize);
+        let scalar_params = RnsParameters::<Bn256, Fr>::new_optimal(&mut cs, 80usize);
+        let mut rng = rand::thread_rng();
+
+        let a: G1Affine = rng.gen();
+        let scalar : Fr = rng.gen();
+        let mut tmp = a.into_projective();
+        tmp.mul_assign(scalar);
+        let result = tmp.into_affine();
+
+        let mut a = AffinePoint::alloc(&mut cs, Some(a), &params).unwrap();
+        let mut scalar = FieldElement::alloc(&mut cs, Some(scalar), &scalar_params).unwrap();
+        let mut actual_result = AffinePoint::alloc(&mut cs, Some(result), &params).unwrap();
+
+        let mut by_scalar = a.mul_by_scalar_for_composite_order_curve(&mut cs, &mut scalar).unwrap();
+        let digits = scalar.decompose_into_skewed_representation(&mut cs).unwrap();
+        let mut by_digits = a.mul_by_skewed_digits(&mut cs, &digits).unwrap();
+
+        assert_eq!(by_scalar.get_value(), by_digits.get_value());
+        AffinePoint::enforce_equal(&mut cs, &mut by_scalar, &mut by_digits).unwrap();
+        AffinePoint::enforce_equal(&mut cs, &mut by_digits, &mut actual_result).unwrap();
+        assert!(cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_mul_by_constant_one_and_zero() {
+        let mut cs = TrivialAssembly::<Bn256, Width4WithCustomGates, SelectorOptimizedWidth4MainGateWithDNext>::new();
+        inscribe_default_bitop_range_table(&mut cs).unwrap();
+        let params = RnsParameters::<Bn256, Fq>::new_optimal(&mut cs, 80usize);
+        let scalar_params = RnsParameters::<Bn256, Fr>::new_optimal(&mut cs, 80usize);
+        let mut rng = rand::thread_rng();
+
+        let a_wit: G1Affine = rng.gen();
+        let mut a = AffinePoint::alloc(&mut cs, Some(a_wit), &params).unwrap();
+        let mut one = FieldElement::constant(Fr::one(), &scalar_params);
+        let mut zero = FieldElement::constant(Fr::zero(), &scalar_params);
+
+        let start = cs.get_current_step_number();
+        let mut res = a.mul_by_scalar_for_composite_order_curve(&mut cs, &mut one).unwrap();
+        let _ = a.mul_by_scalar_for_prime_order_curve(&mut cs, &mut one).unwrap();
+        let end = cs.get_current_step_number();
+        assert_eq!(end - start, 0);
+        assert_eq!(res.get_value().unwrap(), a_wit);
+
+        let res_zero = a.mul_by_scalar_for_composite_order_curve(&mut cs, &mut zero).unwrap();
+        assert_eq!(res_zero.is_infinity.get_value(), Some(true));
+
+        AffinePoint::enforce_equal(&mut cs, &mut res, &mut a).unwrap();
+        assert!(cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_affine_multiexp() {
+        let mut cs = TrivialAssembly::<Bn256, Width4WithCustomGates, SelectorOptimizedWidth4MainGateWithDNext>::new();
+        inscribe_default_bitop_range_table(&mut cs).unwrap();
+        let params = RnsParameters::<Bn256, Fq>::new_optimal(&mut cs, 80usize);
+        let scalar_params = RnsParameters::<Bn256, Fr>::new_optimal(&mut cs, 80usize);
+        let mut rng = rand::thread_rng();
+
+        let num_points = 4;
+        let mut points = Vec::with_capacity(num_points);
+        let mut scalars = Vec::with_capacity(num_points);
+        let mut expected = G1Affine::zero().into_projective();
+        for _ in 0..num_points {
+            let point: G1Affine = rng.gen();
+            let scalar: Fr = rng.gen();
+            let mut tmp = point.into_projective();
+            tmp.mul_assign(scalar);
+            expected.add_assign(&tmp);
+
+            points.push(AffinePoint::alloc(&mut cs, Some(point), &params).unwrap());
+            scalars.push(FieldElement::alloc(&mut cs, Some(scalar), &scalar_params).unwrap());
+        }
+        let mut actual_result = AffinePoint::alloc(&mut cs, Some(expected.into_affine()), &params).unwrap();
+
+        let multiexp_start = cs.get_current_step_number();
+        let result = AffinePoint::multiexp(&mut cs, &mut scalars, &mut points).unwrap();
+        let multiexp_end = cs.get_current_step_number();
+        println!("num of gates: {}", multiexp_end - multiexp_start);
+
+        let mut result = unsafe { result.convert_to_affine(&mut cs).unwrap() };
+        AffinePoint::enforce_equal(&mut cs, &mut result, &mut actual_result).unwrap();
+        assert!(cs.is_satisfied());
+
+        assert!(AffinePoint::multiexp(&mut cs, &mut scalars[..3], &mut points).is_err());
+    }
+
+    #[test]
+    fn test_masked_multiexp() {
+        let mut cs = TrivialAssembly::<Bn256, Width4WithCustomGates, SelectorOptimizedWidth4MainGateWithDNext>::new();
+        inscribe_default_bitop_range_table(&mut cs).unwrap();
+        let params = RnsParameters::<Bn256, Fq>::new_optimal(&mut cs, 80usize);
+        let scalar_params = RnsParameters::<Bn256, Fr>::new_optimal(&mut cs, 80usize);
+        let mut rng = rand::thread_rng();
+
+        let mut terms = vec![];
+        let mut expected = G1Affine::zero().into_projective();
+        for &include in [true, false, true].iter() {
+            let point: G1Affine = rng.gen();
+            let scalar: Fr = rng.gen();
+            if include {
+                expected.add_assign_mixed(&AffinePoint::<Bn256, G1Affine>::expected_scalar_mul(point, scalar));
+            }
+            terms.push((
+                AffinePoint::alloc(&mut cs, Some(point), &params).unwrap(),
+                FieldElement::alloc(&mut cs, Some(scalar), &scalar_params).unwrap(),
+                Boolean::from(AllocatedBit::alloc(&mut cs, Some(include)).unwrap())
+            ));
+        }
+
+        let mut res = AffinePoint::masked_multiexp(&mut cs, &mut terms).unwrap();
+        assert_eq!(res.get_value(), Some(expected.into_affine()));
+        assert_eq!(res.is_infinity.get_value(), Some(false));
+        let mut actual = AffinePoint::alloc(&mut cs, Some(expected.into_affine()), &params).unwrap();
+        AffinePoint::enforce_equal(&mut cs, &mut res, &mut actual).unwrap();
+        assert!(cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_multiexp_cost_and_zero_scalars() {
+        let mut cs = TrivialAssembly::<Bn256, Width4WithCustomGates, SelectorOptimizedWidth4MainGateWithDNext>::new();
+        inscribe_default_bitop_range_table(&mut cs).unwrap();
+        let params = RnsParameters::<Bn256, Fq>::new_optimal(&mut cs, 80usize);
+        let scalar_params = RnsParameters::<Bn256, Fr>::new_optimal(&mut cs, 80usize);
+        let mut rng = rand::thread_rng();
+
+        for &num_points in [2usize, 3].iter() {
+            let mut points = Vec::with_capacity(num_points);
+            let mut scalars = Vec::with_capacity(num_points);
+            for _ in 0..num_points {
+                let point: G1Affine = rng.gen();
+                let scalar: Fr = rng.gen();
+                points.push(AffinePoint::alloc(&mut cs, Some(point), &params).unwrap());
+                scalars.push(FieldElement::alloc(&mut cs, Some(scalar), &scalar_params).unwrap());
+            }
+
+            let start = cs.get_current_step_number();
+            let _ = AffinePoint::multiexp(&mut cs, &mut scalars, &mut points).unwrap();
+            let multiexp_gates = cs.get_current_step_number() - start;
+
+            let start = cs.get_current_step_number();
+            for (point, scalar) in points.iter_mut().zip(scalars.iter_mut()) {
+                let _ = point.mul_by_scalar_for_prime_order_curve(&mut cs, scalar).unwrap();
+            }
+            let naive_gates = cs.get_current_step_number() - start;
+            println!("{} points: multiexp {} gates, naive {} gates", num_points, multiexp_gates, naive_gates);
+            assert!(multiexp_gates < naive_gates);
+        }
+
+        // one of the scalars is zero: allocated zero goes through the ladder, constant zero is skipped
+        let p_wit: G1Affine = rng.gen();
+        let q_wit: G1Affine = rng.gen();
+        let scalar_wit: Fr = rng.gen();
+        let expected = AffinePoint::<Bn256, G1Affine>::expected_scalar_mul(p_wit, scalar_wit);
+        let zeroes = vec![
+            FieldElement::alloc(&mut cs, Some(Fr::zero()), &scalar_params).unwrap(),
+            FieldElement::constant(Fr::zero(), &scalar_params)
+        ];
+        for zero in zeroes.into_iter() {
+            let mut points = vec![
+                AffinePoint::alloc(&mut cs, Some(p_wit), &params).unwrap(),
+                AffinePoint::alloc(&mut cs, Some(q_wit), &params).unwrap()
+            ];
+            let mut scalars = vec![FieldElement::alloc(&mut cs, Some(scalar_wit), &scalar_params).unwrap(), zero];
+            let result = AffinePoint::multiexp(&mut cs, &mut scalars, &mut points).unwrap();
+            let mut result = unsafe { result.convert_to_affine(&mut cs).unwrap() };
+            let mut actual_result = AffinePoint::alloc(&mut cs, Some(expected), &params).unwrap();
+            AffinePoint::enforce_equal(&mut cs, &mut result, &mut actual_result).unwrap();
+        }
+        assert!(cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_expected_scalar_mul() {
+        let mut cs = TrivialAssembly::<Bn256, Width4WithCustomGates, SelectorOptimizedWidth4MainGateWithDNext>::new();
+        inscribe_default_bitop_range_table(&mut cs).unwrap();
+        let params = RnsParameters::<Bn256, Fq>::new_optimal(&mut cs, 80usize);
+        let scalar_params = RnsParameters::<Bn256, Fr>::new_optimal(&mut cs, 80usize);
+        let mut rng = rand::thread_rng();
+
+        let a_wit: G1Affine = rng.gen();
+        let scalar_wit: Fr = rng.gen();
+        let expected = AffinePoint::<Bn256, G1Affine>::expected_scalar_mul(a_wit, scalar_wit);
+
+        let mut a = AffinePoint::alloc(&mut cs, Some(a_wit), &params).unwrap();
+        let mut scalar = FieldElement::alloc(&mut cs, Some(scalar_wit), &scalar_params).unwrap();
+        let mut res = a.mul_by_scalar_for_composite_order_curve(&mut cs, &mut scalar).unwrap();
+        assert_eq!(res.get_value(), Some(expected));
+
+        let mut actual_result = AffinePoint::alloc(&mut cs, Some(expected), &params).unwrap();
+        AffinePoint::enforce_equal(&mut cs, &mut res, &mut actual_result).unwrap();
+        assert!(cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_naf_decomposition() {
+        for m in [1u64, 2, 3, 7, 11, 255, 1000001].iter() {
+            let digits = compute_naf(&BigUint::from(*m));
+            let mut acc = 0i64;
+            for (i, d) in digits.iter().enumerate() {
+                acc += (*d as i64) << i;
+            }
+            assert_eq!(acc, *m as i64);
+            assert_eq!(*digits.last().unwrap(), 1);
+            for pair in digits.windows(2) {
+                assert!(pair[0] == 0 || pair[1] == 0);
+            }
+        }
+        assert!(compute_naf(&BigUint::from(0u64)).is_empty());
+    }
+
+    #[test]
+    fn test_mul_by_constant_scalar() {
+        let mut cs = TrivialAssembly::<Bn256, Width4WithCustomGates, SelectorOptimizedWidth4MainGateWithDNext>::new();
+        inscribe_default_bitop_range_table(&mut cs).unwrap();
+        let params = RnsParameters::<Bn256, Fq>::new_optimal(&mut cs, 80usize);
+        let scalar_params = RnsParameters::<Bn256, Fr>::new_optimal(&mut cs, 80usize);
+        let mut rng = rand::thread_rng();
+
+        let a_wit: G1Affine = rng.gen();
+        let scalar_wit: Fr = rng.gen();
+        let result = AffinePoint::<Bn256, G1Affine>::expected_scalar_mul(a_wit, scalar_wit);
+
+        let mut a = AffinePoint::alloc(&mut cs, Some(a_wit), &params).unwrap();
+        let mut constant_scalar = FieldElement::constant(scalar_wit, &scalar_params);
+        let mut variable_scalar = FieldElement::alloc(&mut cs, Some(scalar_wit), &scalar_params).unwrap();
+        let mut actual_result = AffinePoint::alloc(&mut cs, Some(result), &params).unwrap();
+
+        let start = cs.get_current_step_number();
+        let mut res = a.mul_by_scalar_for_composite_order_curve(&mut cs, &mut constant_scalar).unwrap();
+        let constant_mul_gates = cs.get_current_step_number() - start;
+        let start = cs.get_current_step_number();
+        let _ = a.mul_by_scalar_for_composite_order_curve(&mut cs, &mut variable_scalar).unwrap();
+        let variable_mul_gates = cs.get_current_step_number() - start;
+        println!("constant scalar: {} gates, variable scalar: {} gates", constant_mul_gates, variable_mul_gates);
+
+        assert_eq!(res.get_value(), Some(result));
+        AffinePoint::enforce_equal(&mut cs, &mut res, &mut actual_result).unwrap();
+        assert!(cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_finalize_for_export() {
+        let mut cs = TrivialAssembly::<Bn256, Width4WithCustomGates, SelectorOptimizedWidth4MainGateWithDNext>::new();
+        inscribe_default_bitop_range_table(&mut cs).unwrap();
+        let params = RnsParameters::<Bn256, Fq>::new_optimal(&mut cs, 80usize);
+        let scalar_params = RnsParameters::<Bn256, Fr>::new_optimal(&mut cs, 80usize);
+        let mut rng = rand::thread_rng();
+
+        let a: G1Affine = rng.gen();
+        let scalar : Fr = rng.gen();
+        let mut tmp = a.into_projective();
+        tmp.mul_assign(scalar);
+        let expected = tmp.into_affine();
+
+        let mut a = AffinePoint::alloc(&mut cs, Some(a), &params).unwrap();
+        let mut scalar = FieldElement::alloc(&mut cs, Some(scalar), &scalar_params).unwrap();
+        let result = a.mul_by_scalar_for_prime_order_curve(&mut cs, &mut scalar).unwrap();
+        let mut result = unsafe { result.convert_to_affine(&mut cs).unwrap() };
+
+        let limbs = result.finalize_for_export(&mut cs).unwrap();
+        assert!(limbs.iter().all(|x| x.get_value().is_some()));
+        assert_eq!(result.x.get_reduction_status(), ReductionStatus::Normalized);
+        assert_eq!(result.y.get_reduction_status(), ReductionStatus::Normalized);
+        assert_eq!(result.get_value().unwrap(), expected);
+        result.enforce_if_normalized(&mut cs).unwrap();
+        assert!(cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_skewed_ladder_negation_strategies() {
+        let mut cs = TrivialAssembly::<Bn256, Width4WithCustomGates, SelectorOptimizedWidth4MainGateWithDNext>::new();
+        inscribe_default_bitop_range_table(&mut cs).unwrap();
+        let params = RnsParameters::<Bn256, Fq>::new_optimal(&mut cs, 80usize);
+        let scalar_params = RnsParameters::<Bn256, Fr>::new_optimal(&mut cs, 80usize);
+        let mut rng = rand::thread_rng();
+
+        let a: G1Affine = rng.gen();
+        let scalar : Fr = rng.gen();
+        let mut a = AffinePoint::alloc(&mut cs, Some(a), &params).unwrap();
+        let mut scalar = FieldElement::alloc(&mut cs, Some(scalar), &scalar_params).unwrap();
+        let digits = scalar.decompose_into_skewed_representation(&mut cs).unwrap();
+
+        let select_start = cs.get_current_step_number();
+        let mut via_select = a.mul_by_skewed_digits(&mut cs, &digits).unwrap();
+        let select_end = cs.get_current_step_number();
+        let mut via_negation = a.mul_by_skewed_digits_with_conditional_negation(&mut cs, &digits).unwrap();
+        let negation_end = cs.get_current_step_number();
+        // the single precomputed -y is cheaper than the negation for every digit
+        assert!(select_end - select_start < negation_end - select_end);
+
+        assert_eq!(via_select.get_value(), via_negation.get_value());
+        AffinePoint::enforce_equal(&mut cs, &mut via_select, &mut via_negation).unwrap();
+        assert!(cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_composite_order_ladder_accumulators() {
+        let mut cs = TrivialAssembly::<Bn256, Width4WithCustomGates, SelectorOptimizedWidth4MainGateWithDNext>::new();
+        inscribe_default_bitop_range_table(&mut cs).unwrap();
+        let params = RnsParameters::<Bn256, Fq>::new_optimal(&mut cs, 80usize);
+        let scalar_params = RnsParameters::<Bn256, Fr>::new_optimal(&mut cs, 80usize);
+        let mut rng = rand::thread_rng();
+
+        let a: G1Affine = rng.gen();
+        let scalar : Fr = rng.gen();
+        let mut tmp = a.into_projective();
+        tmp.mul_assign(scalar);
+        let result = tmp.into_affine();
+
+        let mut a = AffinePoint::alloc(&mut cs, Some(a), &params).unwrap();
+        let mut scalar = FieldElement::alloc(&mut cs, Some(scalar), &scalar_params).unwrap();
+        let mut actual_result = AffinePoint::alloc(&mut cs, Some(result), &params).unwrap();
+
+        let affine_start = cs.get_current_step_number();
+        let mut via_affine = a.mul_by_scalar_for_composite_order_curve_ext(&mut cs, &mut scalar, false).unwrap();
+        let affine_end = cs.get_current_step_number();
+        let mut via_projective = a.mul_by_scalar_for_composite_order_curve_ext(&mut cs, &mut scalar, true).unwrap();
+        let projective_end = cs.get_current_step_number();
+        // inversions are cheap: one double_and_add per digit beats projective doubling and mixed addition
+        assert!(affine_end - affine_start < projective_end - affine_end);
+        assert_eq!(via_projective.is_infinity.get_value(), Some(false));
+
+        AffinePoint::enforce_equal(&mut cs, &mut via_affine, &mut actual_result).unwrap();
+        AffinePoint::enforce_equal(&mut cs, &mut via_projective, &mut actual_result).unwrap();
+        assert!(cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_offset_generator_dst_from_curve_params() {
+        let mut cs = TrivialAssembly::<Bn256, Width4WithCustomGates, SelectorOptimizedWidth4MainGateWithDNext>::new();
+        inscribe_default_bitop_range_table(&mut cs).unwrap();
+        let base_params = RnsParameters::<Bn256, Fq>::new_optimal(&mut cs, 80usize);
+        let scalar_params = RnsParameters::<Bn256, Fr>::new_optimal(&mut cs, 80usize);
+        let default_params = CurveCircuitParameters::<Bn256, G1Affine>::new(base_params, scalar_params, true);
+        let mut custom_params = default_params.clone();
+        custom_params.set_offset_generator_dst(b"Custom_DST");
+
+        let default_generator = AffinePoint::<Bn256, G1Affine>::offset_generator_from_dst(&crate::constants::MULTIEXP_DST[..]);
+        assert_eq!(default_params.offset_generator(), default_generator);
+        assert!(custom_params.offset_generator() != default_generator);
+        assert_eq!(custom_params.offset_generator(), AffinePoint::<Bn256, G1Affine>::offset_generator_from_dst(b"Custom_DST"));
+
+        let mut rng = rand::thread_rng();
+        let a_wit: G1Affine = rng.gen();
+        let scalar_wit: Fr = rng.gen();
+        let mut expected = a_wit.into_projective();
+        expected.mul_assign(scalar_wit);
+        for curve_params in vec![&default_params, &custom_params].into_iter() {
+            let mut a = AffinePoint::alloc(&mut cs, Some(a_wit), &curve_params.base_field_rns_params).unwrap();
+            let mut scalar = FieldElement::alloc(&mut cs, Some(scalar_wit), &curve_params.scalar_field_rns_params).unwrap();
+            let res = a.mul_by_scalar_for_composite_order_curve_with_params(&mut cs, &mut scalar, curve_params).unwrap();
+            assert_eq!(res.get_value(), Some(expected.into_affine()));
+        }
+        assert!(cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_offset_generator_is_deterministic() {
+        let first = AffinePoint::<Bn256, G1Affine>::offset_generator_from_dst(&crate::constants::MULTIEXP_DST[..]);
+        let second = AffinePoint::<Bn256, G1Affine>::offset_generator_from_dst(&crate::constants::MULTIEXP_DST[..]);
+        assert_eq!(first, second);
+        let other = AffinePoint::<Bn256, G1Affine>::offset_generator_from_dst(b"Other_DST");
+        assert!(first != other);
+
+        // the result doesn't depend on the choice of the offset generator
+        let mut cs = TrivialAssembly::<Bn256, Width4WithCustomGates, SelectorOptimizedWidth4MainGateWithDNext>::new();
+        inscribe_default_bitop_range_table(&mut cs).unwrap();
+        let params = RnsParameters::<Bn256, Fq>::new_optimal(&mut cs, 80usize);
+        let scalar_params = RnsParameters::<Bn256, Fr>::new_optimal(&mut cs, 80usize);
+        let mut rng = rand::thread_rng();
+
+        let a: G1Affine = rng.gen();
+        let scalar : Fr = rng.gen();
+        let mut a = AffinePoint::alloc(&mut cs, Some(a), &params).unwrap();
+        let mut scalar = FieldElement::alloc(&mut cs, Some(scalar), &scalar_params).unwrap();
+        let digits = scalar.decompose_into_skewed_representation(&mut cs).unwrap();
+
+        let mut res = a.mul_by_skewed_digits(&mut cs, &digits).unwrap();
+        let mut res_with_other = a.mul_by_skewed_digits_with_offset_generator(&mut cs, &digits, other).unwrap();
+        AffinePoint::enforce_equal(&mut cs, &mut res, &mut res_with_other).unwrap();
+        assert!(cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_windowed_mul_with_auto_window() {
+        type Point<'a> = AffinePoint<'a, Bn256, G1Affine>;
+        // computed by hand from the cost model for 256-bit scalars and 4 limbs: 
+        // window of 2 costs 104813 gates, window of 3 - 93382 gates and window of 4 - 89627 gates 
+        assert_eq!(Point::estimate_windowed_mul_cost(256, 2, 4), 104813);
+        assert_eq!(Point::estimate_windowed_mul_cost(256, 3, 4), 93382);
+        assert_eq!(Point::estimate_windowed_mul_cost(256, 4, 4), 89627);
+        assert_eq!(Point::optimal_window_size(256, 4), 4);
+        // short scalars lead to smaller windows
+        assert_eq!(Point::optimal_window_size(8, 4), 2);
+
+        let mut cs = TrivialAssembly::<Bn256, Width4WithCustomGates, SelectorOptimizedWidth4MainGateWithDNext>::new();
+        inscribe_default_bitop_range_table(&mut cs).unwrap();
+        let params = RnsParameters::<Bn256, Fq>::new_optimal(&mut cs, 80usize);
+        let scalar_params = RnsParameters::<Bn256, Fr>::new_optimal(&mut cs, 80usize);
+        assert_eq!(params.get_num_binary_limbs(), 4);
+        let mut rng = rand::thread_rng();
+        let num_bits = Fr::NUM_BITS as usize;
+
+        let a_wit: G1Affine = rng.gen();
+        let scalar_wit : Fr = rng.gen();
+        let mut a = AffinePoint::alloc(&mut cs, Some(a_wit), &params).unwrap();
+        let mut scalar = FieldElement::alloc(&mut cs, Some(scalar_wit), &scalar_params).unwrap();
+        let mut actual_result = AffinePoint::alloc(
+            &mut cs, Some(Point::expected_scalar_mul(a_wit, scalar_wit)), &params
+        ).unwrap();
+        let windowed_mul_start = cs.get_current_step_number();
+        let result = a.windowed_mul(&mut cs, &mut scalar, num_bits).unwrap();
+        let windowed_mul_end = cs.get_current_step_number();
+        println!("num of gates: {}", windowed_mul_end - windowed_mul_start);
+        let mut result = unsafe { result.convert_to_affine(&mut cs).unwrap() };
+        AffinePoint::enforce_equal(&mut cs, &mut result, &mut actual_result).unwrap();
+
+        // the model is a rough one (it doesn't account for the decomposition of the scalar), 
+        // but it should stay within a factor of two of the actual cost
+        for window in 2..=5 {
+            let start = cs.get_current_step_number();
+            let _ = a.windowed_mul_with_window(&mut cs, &mut scalar, window).unwrap();
+            let measured = cs.get_current_step_number() - start;
+            let estimated = Point::estimate_windowed_mul_cost(num_bits, window, 4);
+            println!("window {}: {} gates measured, {} gates estimated", window, measured, estimated);
+            assert!(measured <= 2 * estimated && estimated <= 2 * measured);
+        }
+
+        // 16-bit scalar: the high bits are enforced to be zero and skipped
+        let small_scalar_wit = Fr::from_str("40503").unwrap();
+        let mut small_scalar = FieldElement::alloc(&mut cs, Some(small_scalar_wit), &scalar_params).unwrap();
+        let mut expected = AffinePoint::alloc(
+            &mut cs, Some(Point::expected_scalar_mul(a_wit, small_scalar_wit)), &params
+        ).unwrap();
+        let start = cs.get_current_step_number();
+        let result = a.windowed_mul(&mut cs, &mut small_scalar, 16).unwrap();
+        println!("num of gates for 16-bit scalar: {}", cs.get_current_step_number() - start);
+        let mut result = unsafe { result.convert_to_affine(&mut cs).unwrap() };
+        AffinePoint::enforce_equal(&mut cs, &mut result, &mut expected).unwrap();
+        assert!(cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_batch_equals() {
+        let mut cs = TrivialAssembly::<Bn256, Width4WithCustomGates, SelectorOptimizedWidth4MainGateWithDNext>::new();
+        inscribe_default_bitop_range_table(&mut cs).unwrap();
+        let params = RnsParameters::<Bn256, Fq>::new_optimal(&mut cs, 80usize);
+        let mut rng = rand::thread_rng();
+
+        let values: Vec<G1Affine> = (0..3).map(|_| rng.gen()).collect();
+        let other_value: G1Affine = rng.gen();
+        let mut modified_values = values.clone();
+        modified_values[1] = other_value;
+
+        let mut first = vec![];
+        let mut second = vec![];
+        let mut third = vec![];
+        for (value, modified_value) in values.iter().zip(modified_values.iter()) {
+            first.push(AffinePoint::alloc(&mut cs, Some(*value), &params).unwrap());
+            second.push(AffinePoint::alloc(&mut cs, Some(*value), &params).unwrap());
+            third.push(AffinePoint::alloc(&mut cs, Some(*modified_value), &params).unwrap());
+        }
+
+        let matching = AffinePoint::batch_equals(&mut cs, &mut first, &mut second).unwrap();
+        let mismatching = AffinePoint::batch_equals(&mut cs, &mut first, &mut third).unwrap();
+        assert_eq!(matching.get_value(), Some(true));
+        assert_eq!(mismatching.get_value(), Some(false));
+        assert!(AffinePoint::batch_equals(&mut cs, &mut first[..2], &mut second).is_err());
+        assert!(cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_sum_complete() {
+        let mut cs = TrivialAssembly::<Bn256, Width4WithCustomGates, SelectorOptimizedWidth4MainGateWithDNext>::new();
+        inscribe_default_bitop_range_table(&mut cs).unwrap();
+        let params = RnsParameters::<Bn256, Fq>::new_optimal(&mut cs, 80usize);
+        let mut rng = rand::thread_rng();
+
+        let empty_sum = AffinePoint::<Bn256, G1Affine>::sum_complete(&mut cs, &[], &params).unwrap();
+        assert_eq!(empty_sum.is_infinity.get_value(), Some(true));
+
+        let p_wit: G1Affine = rng.gen();
+        let q_wit: G1Affine = rng.gen();
+        let mut p = AffinePoint::alloc(&mut cs, Some(p_wit), &params).unwrap();
+        let q = AffinePoint::alloc(&mut cs, Some(q_wit), &params).unwrap();
+
+        let mut single_sum = AffinePoint::sum_complete(&mut cs, &[p.clone()], &params).unwrap();
+        AffinePoint::enforce_equal(&mut cs, &mut single_sum, &mut p).unwrap();
+
+        // P + P + Q contains the duplicate
+        let mut tmp = p_wit.into_projective();
+        tmp.double();
+        tmp.add_assign_mixed(&q_wit);
+        let mut expected = AffinePoint::alloc(&mut cs, Some(tmp.into_affine()), &params).unwrap();
+        let mut sum = AffinePoint::sum_complete(&mut cs, &[p.clone(), p.clone(), q.clone()], &params).unwrap();
+        assert_eq!(sum.is_infinity.get_value(), Some(false));
+        assert_eq!(sum.get_value().unwrap(), tmp.into_affine());
+        AffinePoint::enforce_equal(&mut cs, &mut sum, &mut expected).unwrap();
+
+        // P + (-P) is the point at infinity
+        let minus_p = p.negate(&mut cs).unwrap();
+        let opposite_sum = AffinePoint::sum_complete(&mut cs, &[p.clone(), minus_p], &params).unwrap();
+        assert_eq!(opposite_sum.is_infinity.get_value(), Some(true));
+        assert!(cs.is_satisfied());
+    }
+
+    #[cfg(feature = "inversion-stats")]
+    #[test]
+    fn test_inversion_count_for_add_unequal() {
+        let mut cs = TrivialAssembly::<Bn256, Width4WithCustomGates, SelectorOptimizedWidth4MainGateWithDNext>::new();
+        inscribe_default_bitop_range_table(&mut cs).unwrap();
+        let params = RnsParameters::<Bn256, Fq>::new_optimal(&mut cs, 80usize);
+        let mut rng = rand::thread_rng();
+
+        let p_wit: G1Affine = rng.gen();
+        let q_wit: G1Affine = rng.gen();
+        let mut p = AffinePoint::alloc(&mut cs, Some(p_wit), &params).unwrap();
+        let mut q = AffinePoint::alloc(&mut cs, Some(q_wit), &params).unwrap();
+
+        reset_inversion_stats();
+        let _ = p.add_unequal(&mut cs, &mut q).unwrap();
+        // the only division computes the slope of the chord
+        assert_eq!(get_num_inversions(), 1);
+        assert!(cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_enforce_equals_constant() {
+        let mut cs = TrivialAssembly::<Bn256, Width4WithCustomGates, SelectorOptimizedWidth4MainGateWithDNext>::new();
+        inscribe_default_bitop_range_table(&mut cs).unwrap();
+        let params = RnsParameters::<Bn256, Fq>::new_optimal(&mut cs, 80usize);
+        let mut rng = rand::thread_rng();
+
+        let p_wit: G1Affine = rng.gen();
+        let mut p = AffinePoint::alloc(&mut cs, Some(p_wit), &params).unwrap();
+        let start = cs.get_current_step_number();
+        p.enforce_equals_constant(&mut cs, p_wit).unwrap();
+        let against_constant_gates = cs.get_current_step_number() - start;
+
+        let mut q = AffinePoint::alloc(&mut cs, Some(p_wit), &params).unwrap();
+        let mut constant = AffinePoint::constant(p_wit, &params);
+        let start = cs.get_current_step_number();
+        AffinePoint::enforce_equal(&mut cs, &mut q, &mut constant).unwrap();
+        let generic_gates = cs.get_current_step_number() - start;
+        // both end up with the same two linear equalities per coordinate
+        assert!(against_constant_gates <= generic_gates);
+        assert!(cs.is_satisfied());
+
+        // constant mismatch is reported at synthesis time
+        let other_wit: G1Affine = rng.gen();
+        let mut constant = AffinePoint::constant(p_wit, &params);
+        assert!(constant.enforce_equals_constant(&mut cs, other_wit).is_err());
+
+        // while the mismatching witness leaves the circuit unsatisfied
+        let mut cs = TrivialAssembly::<Bn256, Width4WithCustomGates, SelectorOptimizedWidth4MainGateWithDNext>::new();
+        inscribe_default_bitop_range_table(&mut cs).unwrap();
+        let params = RnsParameters::<Bn256, Fq>::new_optimal(&mut cs, 80usize);
+        let mut p = AffinePoint::alloc(&mut cs, Some(p_wit), &params).unwrap();
+        p.enforce_equals_constant(&mut cs, other_wit).unwrap();
+        assert!(!cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_uncompressed_encoding_roundtrip() {
+        let mut cs = TrivialAssembly::<Bn256, Width4WithCustomGates, SelectorOptimizedWidth4MainGateWithDNext>::new();
+        inscribe_default_bitop_range_table(&mut cs).unwrap();
+        let params = RnsParameters::<Bn256, Fq>::new_optimal(&mut cs, 80usize);
+
+        // generator of bn256 is (1, 2)
+        let mut expected_encoding = vec![0u8; 64];
+        expected_encoding[31] = 1;
+        expected_encoding[63] = 2;
+
+        let mut generator = AffinePoint::alloc(&mut cs, Some(G1Affine::one()), &params).unwrap();
+        let encoding = generator.to_bytes_uncompressed(&mut cs).unwrap();
+        let encoding_values: Vec<Fr> = encoding.iter().map(|x| x.get_value().unwrap()).collect();
+        let expected_values: Vec<Fr> = expected_encoding.iter().map(|x| u64_to_ff::<Fr>(*x as u64)).collect();
+        assert_eq!(encoding_values, expected_values);
+
+        let allocated_bytes: Vec<Num<Bn256>> = expected_values.iter().map(|x| {
+            Num::Variable(AllocatedNum::alloc(&mut cs, || Ok(*x)).unwrap())
+        }).collect();
+        let mut decoded = AffinePoint::<Bn256, G1Affine>::from_bytes_uncompressed(&mut cs, &allocated_bytes, &params).unwrap();
+        assert_eq!(decoded.get_value().unwrap(), G1Affine::one());
+        AffinePoint::enforce_equal(&mut cs, &mut decoded, &mut generator).unwrap();
+        assert!(cs.is_satisfied());
+
+        // the flag which may be set is enforced in the circuit
+        assert!(AffinePoint::<Bn256, G1Affine>::zero(&params).to_bytes_uncompressed(&mut cs).is_err());
+        generator.is_infinity = Boolean::from(AllocatedBit::alloc(&mut cs, Some(false)).unwrap());
+        let _ = generator.to_bytes_uncompressed(&mut cs).unwrap();
+        assert!(cs.is_satisfied());
+        generator.is_infinity = Boolean::from(AllocatedBit::alloc(&mut cs, Some(true)).unwrap());
+        let _ = generator.to_bytes_uncompressed(&mut cs).unwrap();
+        assert!(!cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_uncompressed_encoding_of_invalid_length() {
+        let mut cs = TrivialAssembly::<Bn256, Width4WithCustomGates, SelectorOptimizedWidth4MainGateWithDNext>::new();
+        inscribe_default_bitop_range_table(&mut cs).unwrap();
+        let params = RnsParameters::<Bn256, Fq>::new_optimal(&mut cs, 80usize);
+
+        let mut generator = AffinePoint::alloc(&mut cs, Some(G1Affine::one()), &params).unwrap();
+        let mut encoding = generator.to_bytes_uncompressed(&mut cs).unwrap();
+        
+        // odd length
+        let last = encoding.pop().unwrap();
+        assert!(AffinePoint::<Bn256, G1Affine>::from_bytes_uncompressed(&mut cs, &encoding, &params).is_err());
+        // even length, but the coordinates are too short
+        encoding.pop();
+        assert!(AffinePoint::<Bn256, G1Affine>::from_bytes_uncompressed(&mut cs, &encoding, &params).is_err());
+        // and too long
+        encoding.extend(vec![last; 3]);
+        assert!(AffinePoint::<Bn256, G1Affine>::from_bytes_uncompressed(&mut cs, &encoding, &params).is_err());
+    }
+
+    #[test]
+    fn test_equals_generator() {
+        let mut cs = TrivialAssembly::<Bn256, Width4WithCustomGates, SelectorOptimizedWidth4MainGateWithDNext>::new();
+        inscribe_default_bitop_range_table(&mut cs).unwrap();
+        let params = RnsParameters::<Bn256, Fq>::new_optimal(&mut cs, 80usize);
+        let mut rng = rand::thread_rng();
+
+        let mut generator = AffinePoint::alloc(&mut cs, Some(G1Affine::one()), &params).unwrap();
+        let p_wit: G1Affine = rng.gen();
+        let mut p = AffinePoint::alloc(&mut cs, Some(p_wit), &params).unwrap();
+
+        let is_generator = generator.equals_generator(&mut cs).unwrap();
+        let p_is_generator = p.equals_generator(&mut cs).unwrap();
+        assert_eq!(is_generator.get_value(), Some(true));
+        assert_eq!(p_is_generator.get_value(), Some(false));
+        assert!(cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_is_zero_for_opposite_points() {
+        let mut cs = TrivialAssembly::<Bn256, Width4WithCustomGates, SelectorOptimizedWidth4MainGateWithDNext>::new();
+        inscribe_default_bitop_range_table(&mut cs).unwrap();
+        let params = RnsParameters::<Bn256, Fq>::new_optimal(&mut cs, 80usize);
+        let mut rng = rand::thread_rng();
+
+        let p_wit: G1Affine = rng.gen();
+        let mut p = AffinePoint::alloc(&mut cs, Some(p_wit), &params).unwrap();
+        let n = cs.get_current_step_number();
+        let p_is_zero = p.is_zero(&mut cs).unwrap();
+        assert_eq!(cs.get_current_step_number(), n);
+        assert!(p_is_zero.is_constant());
+        assert_eq!(p_is_zero.get_value(), Some(false));
+        assert_eq!(AffinePoint::zero(&params).is_zero(&mut cs).unwrap().get_value(), Some(true));
+
+        // once the flag is allocated, P + (-P) is resolved into the point at infinity
+        p.is_infinity = Boolean::from(AllocatedBit::alloc(&mut cs, Some(false)).unwrap());
+        let mut minus_p = p.negate(&mut cs).unwrap();
+        let sum = p.add_unequal(&mut cs, &mut minus_p).unwrap();
+        assert_eq!(sum.is_zero(&mut cs).unwrap().get_value(), Some(true));
+        assert_eq!(sum.get_value(), Some(G1Affine::zero()));
+        assert!(cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_equality_respects_infinity_flag() {
+        let mut cs = TrivialAssembly::<Bn256, Width4WithCustomGates, SelectorOptimizedWidth4MainGateWithDNext>::new();
+        inscribe_default_bitop_range_table(&mut cs).unwrap();
+        let params = RnsParameters::<Bn256, Fq>::new_optimal(&mut cs, 80usize);
+        let mut rng = rand::thread_rng();
+
+        let p_wit: G1Affine = rng.gen();
+        let q_wit: G1Affine = rng.gen();
+        let mut p = AffinePoint::alloc(&mut cs, Some(p_wit), &params).unwrap();
+        let mut q = AffinePoint::alloc(&mut cs, Some(q_wit), &params).unwrap();
+        p.is_infinity = Boolean::from(AllocatedBit::alloc(&mut cs, Some(false)).unwrap());
+        q.is_infinity = Boolean::from(AllocatedBit::alloc(&mut cs, Some(false)).unwrap());
+
+        // P + (-P) and Q + (-Q): both are points at infinity, though their coordinates differ
+        let mut minus_p = p.negate(&mut cs).unwrap();
+        let mut minus_q = q.negate(&mut cs).unwrap();
+        let mut first_inf = p.add_unequal(&mut cs, &mut minus_p).unwrap();
+        let mut second_inf = q.add_unequal(&mut cs, &mut minus_q).unwrap();
+        assert_eq!(AffinePoint::equals(&mut cs, &mut first_inf, &mut second_inf).unwrap().get_value(), Some(true));
+        AffinePoint::enforce_equal(&mut cs, &mut first_inf, &mut second_inf).unwrap();
+
+        let mut zero = AffinePoint::zero(&params);
+        let mut other_zero = AffinePoint::zero(&params);
+        let check = AffinePoint::equals(&mut cs, &mut zero, &mut other_zero).unwrap();
+        assert!(check.is_constant() && check.get_constant_value());
+
+        // infinity is not equal to a regular point
+        assert_eq!(AffinePoint::equals(&mut cs, &mut first_inf, &mut p).unwrap().get_value(), Some(false));
+        let check = AffinePoint::equals(&mut cs, &mut zero, &mut minus_p.clone()).unwrap();
+        assert_eq!(check.get_value(), Some(false));
+
+        // regular points with different coordinates
+        assert_eq!(AffinePoint::equals(&mut cs, &mut p, &mut q).unwrap().get_value(), Some(false));
+        let mut p_copy = AffinePoint::alloc(&mut cs, Some(p_wit), &params).unwrap();
+        assert_eq!(AffinePoint::equals(&mut cs, &mut p, &mut p_copy).unwrap().get_value(), Some(true));
+        AffinePoint::enforce_equal(&mut cs, &mut p, &mut p_copy).unwrap();
+        assert!(cs.is_satisfied());
+
+        // unequal points lead to unsatisfied circuit rather than abort the synthesis
+        AffinePoint::enforce_equal(&mut cs, &mut first_inf, &mut p).unwrap();
+        assert!(!cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_double_and_add_reports_intermediate_collision() {
+        let mut cs = TrivialAssembly::<Bn256, Width4WithCustomGates, SelectorOptimizedWidth4MainGateWithDNext>::new();
+        inscribe_default_bitop_range_table(&mut cs).unwrap();
+        let params = RnsParameters::<Bn256, Fq>::new_optimal(&mut cs, 80usize);
+        let mut rng = rand::thread_rng();
+
+        // for Q = -2P we have x(Q) != x(P), but P + Q = -P
+        let p_wit: G1Affine = rng.gen();
+        let mut q_wit = p_wit.into_projective();
+        q_wit.double();
+        q_wit.negate();
+        let q_wit = q_wit.into_affine();
+
+        let mut p = AffinePoint::alloc(&mut cs, Some(p_wit), &params).unwrap();
+        let mut q = AffinePoint::alloc(&mut cs, Some(q_wit), &params).unwrap();
+        assert!(p.double_and_add(&mut cs, &mut q).is_err());
+    }
+
+    #[test]
+    fn test_raw_limbs_round_trip() {
+        let mut cs = TrivialAssembly::<Bn256, Width4WithCustomGates, SelectorOptimizedWidth4MainGateWithDNext>::new();
+        inscribe_default_bitop_range_table(&mut cs).unwrap();
+        let params = RnsParameters::<Bn256, Fq>::new_optimal(&mut cs, 80usize);
+        let mut rng = rand::thread_rng();
+
+        let a_wit: G1Affine = rng.gen();
+        let b_wit: G1Affine = rng.gen();
+        let mut a = AffinePoint::alloc(&mut cs, Some(a_wit), &params).unwrap();
+        let mut b = AffinePoint::alloc(&mut cs, Some(b_wit), &params).unwrap();
+        // the sum has unreduced coordinates, finalize_for_export makes the limbs canonical
+        let mut sum = a.add_unequal(&mut cs, &mut b).unwrap();
+        let limbs = sum.finalize_for_export(&mut cs).unwrap();
+        assert_eq!(limbs.len(), 2 * params.get_num_binary_limbs());
+
+        for check_on_curve in vec![false, true].into_iter() {
+            let mut restored = AffinePoint::<Bn256, G1Affine>::from_raw_limbs_representation(
+                &mut cs, &limbs, &params, check_on_curve
+            ).unwrap();
+            assert_eq!(restored.get_value(), sum.get_value());
+            AffinePoint::enforce_equal(&mut cs, &mut restored, &mut sum).unwrap();
+        }
+        assert!(cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_enforce_batch_on_curve() {
+        let mut rng = rand::thread_rng();
+        let num_points = 16;
+
+        for invalid_idx in vec![None, Some(7)].into_iter() {
+            let mut cs = TrivialAssembly::<Bn256, Width4WithCustomGates, SelectorOptimizedWidth4MainGateWithDNext>::new();
+            inscribe_default_bitop_range_table(&mut cs).unwrap();
+            let params = RnsParameters::<Bn256, Fq>::new_optimal(&mut cs, 80usize);
+
+            let mut points = Vec::with_capacity(num_points);
+            for i in 0..num_points {
+                let point = if Some(i) == invalid_idx {
+                    let x = FieldElement::alloc(&mut cs, Some(rng.gen()), &params).unwrap();
+                    let y = FieldElement::alloc(&mut cs, Some(rng.gen()), &params).unwrap();
+                    unsafe { AffinePoint::<Bn256, G1Affine>::from_xy_unchecked(x, y) }
+                } else {
+                    AffinePoint::<Bn256, G1Affine>::alloc(&mut cs, Some(rng.gen()), &params).unwrap()
+                };
+                points.push(point);
+            }
+
+            let n = cs.get_current_step_number();
+            AffinePoint::enforce_batch_on_curve(&mut cs, &points).unwrap();
+            let batched_gates = cs.get_current_step_number() - n;
+            assert_eq!(cs.is_satisfied(), invalid_idx.is_none());
+
+            let n = cs.get_current_step_number();
+            AffinePoint::<Bn256, G1Affine>::enforce_curve_equation(&mut cs, &points[0].x, &points[0].y).unwrap();
+            let single_check_gates = cs.get_current_step_number() - n;
+            assert!(batched_gates < num_points * single_check_gates);
+        }
+
+        // points which may be at infinity are rejected
+        let mut cs = TrivialAssembly::<Bn256, Width4WithCustomGates, SelectorOptimizedWidth4MainGateWithDNext>::new();
+        inscribe_default_bitop_range_table(&mut cs).unwrap();
+        let params = RnsParameters::<Bn256, Fq>::new_optimal(&mut cs, 80usize);
+        let mut point = AffinePoint::<Bn256, G1Affine>::alloc(&mut cs, Some(rng.gen()), &params).unwrap();
+        point.is_infinity = Boolean::from(AllocatedBit::alloc(&mut cs, Some(false)).unwrap());
+        assert!(AffinePoint::enforce_batch_on_curve(&mut cs, &[point]).is_err());
+    }
+
+    #[test]
+    fn test_negate_and_conditionally_negate_track_value() {
+        let mut cs = TrivialAssembly::<Bn256, Width4WithCustomGates, SelectorOptimizedWidth4MainGateWithDNext>::new();
+        inscribe_default_bitop_range_table(&mut cs).unwrap();
+        let params = RnsParameters::<Bn256, Fq>::new_optimal(&mut cs, 80usize);
+        let mut rng = rand::thread_rng();
+
+        let p_wit: G1Affine = rng.gen();
+        let mut minus_p_wit = p_wit;
+        minus_p_wit.negate();
+        let p = AffinePoint::alloc(&mut cs, Some(p_wit), &params).unwrap();
+
+        let negated = p.negate(&mut cs).unwrap();
+        assert_eq!(negated.get_value(), Some(minus_p_wit));
+        for (flag_value, expected) in vec![(true, minus_p_wit), (false, p_wit)].into_iter() {
+            let constant_flag = Boolean::constant(flag_value);
+            let allocated_flag = Boolean::from(AllocatedBit::alloc(&mut cs, Some(flag_value)).unwrap());
+            for flag in vec![constant_flag, allocated_flag].into_iter() {
+                let res = p.conditionally_negate(&mut cs, &flag).unwrap();
+                assert_eq!(res.get_value(), Some(expected));
+                assert_eq!(res.y.get_field_value(), Some(expected.into_xy_unchecked().1));
+            }
+        }
+        assert!(cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_assert_same_point_value_across_param_sets() {
+        let mut cs = TrivialAssembly::<Bn256, Width4WithCustomGates, SelectorOptimizedWidth4MainGateWithDNext>::new();
+        inscribe_default_bitop_range_table(&mut cs).unwrap();
+        let params_80 = RnsParameters::<Bn256, Fq>::new_optimal(&mut cs, 80usize);
+        let params_64 = RnsParameters::<Bn256, Fq>::new_optimal(&mut cs, 64usize);
+        assert!(params_80.get_binary_limb_width() != params_64.get_binary_limb_width());
+        let mut rng = rand::thread_rng();
+
+        let a_wit: G1Affine = rng.gen();
+        let b_wit: G1Affine = rng.gen();
+        let a = AffinePoint::alloc(&mut cs, Some(a_wit), &params_80).unwrap();
+        let a_other = AffinePoint::alloc(&mut cs, Some(a_wit), &params_64).unwrap();
+        let b_other = AffinePoint::alloc(&mut cs, Some(b_wit), &params_64).unwrap();
+        assert_same_point_value(&a, &a_other);
+        let mismatch = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| assert_same_point_value(&a, &b_other)));
+        assert!(mismatch.is_err());
+        assert!(cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_lexicographic_order_of_points() {
+        let mut cs = TrivialAssembly::<Bn256, Width4WithCustomGates, SelectorOptimizedWidth4MainGateWithDNext>::new();
+        inscribe_default_bitop_range_table(&mut cs).unwrap();
+        let params = RnsParameters::<Bn256, Fq>::new_optimal(&mut cs, 80usize);
+        let mut rng = rand::thread_rng();
+
+        let mut witnesses: Vec<G1Affine> = (0..3).map(|_| rng.gen()).collect();
+        witnesses.sort_by_key(|p| {
+            let (x, y) = p.into_xy_unchecked();
+            (fe_to_biguint(&x), fe_to_biguint(&y))
+        });
+        let mut points: Vec<_> = witnesses.iter().map(|p| AffinePoint::alloc(&mut cs, Some(*p), &params).unwrap()).collect();
+
+        for i in 0..3 {
+            for j in 0..3 {
+                let (mut a, mut b) = (points[i].clone(), points[j].clone());
+                let flag = AffinePoint::lexicographic_less_than(&mut cs, &mut a, &mut b).unwrap();
+                assert_eq!(flag.get_value(), Some(i < j));
+            }
+        }
+        let (first, rest) = points.split_at_mut(1);
+        AffinePoint::enforce_lexicographic_less_than(&mut cs, &mut first[0], &mut rest[0]).unwrap();
+        let (second, third) = rest.split_at_mut(1);
+        AffinePoint::enforce_lexicographic_less_than(&mut cs, &mut second[0], &mut third[0]).unwrap();
+        assert!(cs.is_satisfied());
+
+        // the wrong order leads to unsatisfied circuit
+        let (mut a, mut b) = (points[2].clone(), points[0].clone());
+        AffinePoint::enforce_lexicographic_less_than(&mut cs, &mut a, &mut b).unwrap();
+        assert!(!cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_select_or_negate() {
+        let mut cs = TrivialAssembly::<Bn256, Width4WithCustomGates, SelectorOptimizedWidth4MainGateWithDNext>::new();
+        inscribe_default_bitop_range_table(&mut cs).unwrap();
+        let params = RnsParameters::<Bn256, Fq>::new_optimal(&mut cs, 80usize);
+        let mut rng = rand::thread_rng();
+
+        let p_wit: G1Affine = rng.gen();
+        let q_wit: G1Affine = rng.gen();
+        let p = AffinePoint::alloc(&mut cs, Some(p_wit), &params).unwrap();
+        let q = AffinePoint::alloc(&mut cs, Some(q_wit), &params).unwrap();
+
+        for &(select, negate) in [(false, false), (false, true), (true, false), (true, true)].iter() {
+            let select_flag = Boolean::from(AllocatedBit::alloc(&mut cs, Some(select)).unwrap());
+            let negate_flag = Boolean::from(AllocatedBit::alloc(&mut cs, Some(negate)).unwrap());
+            let mut expected = if select { p_wit } else { q_wit };
+            if negate { expected.negate(); }
+
+            let mut res = AffinePoint::select_or_negate(&mut cs, &select_flag, &negate_flag, &p, &q).unwrap();
+            assert_eq!(res.get_value(), Some(expected));
+            let mut actual = AffinePoint::alloc(&mut cs, Some(expected), &params).unwrap();
+            AffinePoint::enforce_equal(&mut cs, &mut res, &mut actual).unwrap();
+        }
+        assert!(cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_enforce_order_divides() {
+        let mut rng = rand::thread_rng();
+        let p_wit: G1Affine = rng.gen();
+        let group_order = repr_to_biguint::<Fr>(&Fr::char());
+
+        // bn256 is of prime order r, so r and any multiple of it are accepted
+        for m in vec![group_order.clone(), group_order.clone() * BigUint::from(3u64)].into_iter() {
+            let mut cs = TrivialAssembly::<Bn256, Width4WithCustomGates, SelectorOptimizedWidth4MainGateWithDNext>::new();
+            inscribe_default_bitop_range_table(&mut cs).unwrap();
+            let params = RnsParameters::<Bn256, Fq>::new_optimal(&mut cs, 80usize);
+            let mut p = AffinePoint::alloc(&mut cs, Some(p_wit), &params).unwrap();
+            p.enforce_order_divides(&mut cs, &m).unwrap();
+            assert!(cs.is_satisfied());
+        }
+
+        let mut cs = TrivialAssembly::<Bn256, Width4WithCustomGates, SelectorOptimizedWidth4MainGateWithDNext>::new();
+        inscribe_default_bitop_range_table(&mut cs).unwrap();
+        let params = RnsParameters::<Bn256, Fq>::new_optimal(&mut cs, 80usize);
+        let mut p = AffinePoint::alloc(&mut cs, Some(p_wit), &params).unwrap();
+        // the order of P does not divide r + 1: [r + 1]P = P is a regular point
+        let res = p.mul_by_biguint_constant(&mut cs, &(group_order + BigUint::from(1u64))).unwrap();
+        assert_eq!(res.is_infinity.get_value(), Some(false));
+        assert_eq!(res.get_value(), Some(p_wit));
+        assert!(cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_conditionally_add_unequal_unchecked() {
+        let mut cs = TrivialAssembly::<Bn256, Width4WithCustomGates, SelectorOptimizedWidth4MainGateWithDNext>::new();
+        inscribe_default_bitop_range_table(&mut cs).unwrap();
+        let params = RnsParameters::<Bn256, Fq>::new_optimal(&mut cs, 80usize);
+        let mut rng = rand::thread_rng();
+
+        let p_wit: G1Affine = rng.gen();
+        let q_wit: G1Affine = rng.gen();
+        let mut sum_wit = p_wit.into_projective();
+        sum_wit.add_assign_mixed(&q_wit);
+        let mut p = AffinePoint::alloc(&mut cs, Some(p_wit), &params).unwrap();
+        let q = AffinePoint::alloc(&mut cs, Some(q_wit), &params).unwrap();
+        let p_copy = AffinePoint::alloc(&mut cs, Some(p_wit), &params).unwrap();
+
+        let on = Boolean::from(AllocatedBit::alloc(&mut cs, Some(true)).unwrap());
+        let off = Boolean::from(AllocatedBit::alloc(&mut cs, Some(false)).unwrap());
+        let mut sum = p.conditionally_add_unequal_unchecked(&mut cs, &on, &q).unwrap();
+        assert_eq!(sum.get_value(), Some(sum_wit.into_affine()));
+        let mut actual_sum = AffinePoint::alloc(&mut cs, Some(sum_wit.into_affine()), &params).unwrap();
+        AffinePoint::enforce_equal(&mut cs, &mut sum, &mut actual_sum).unwrap();
+
+        let mut skipped = p.conditionally_add_unequal_unchecked(&mut cs, &off, &q).unwrap();
+        assert_eq!(skipped.get_value(), Some(p_wit));
+        AffinePoint::enforce_equal(&mut cs, &mut skipped, &mut p).unwrap();
+
+        // coinciding points with the unset flag: no panic, the value is masked off but the circuit holds
+        let mut masked = p.conditionally_add_unequal_unchecked(&mut cs, &off, &p_copy).unwrap();
+        assert!(masked.get_value().is_none());
+        AffinePoint::enforce_equal(&mut cs, &mut masked, &mut p).unwrap();
+        assert!(cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_aggregate_public_keys() {
+        let mut cs = TrivialAssembly::<Bn256, Width4WithCustomGates, SelectorOptimizedWidth4MainGateWithDNext>::new();
+        inscribe_default_bitop_range_table(&mut cs).unwrap();
+        let params = RnsParameters::<Bn256, Fq>::new_optimal(&mut cs, 80usize);
+        let mut rng = rand::thread_rng();
+
+        let num_keys = 4;
+        let mut public_keys = Vec::with_capacity(num_keys);
+        let mut expected = G1Affine::zero().into_projective();
+        for _ in 0..num_keys {
+            let secret_key: Fr = rng.gen();
+            let public_key = AffinePoint::<Bn256, G1Affine>::expected_scalar_mul(G1Affine::one(), secret_key);
+            expected.add_assign_mixed(&public_key);
+            public_keys.push(AffinePoint::alloc(&mut cs, Some(public_key), &params).unwrap());
+        }
+
+        let mut aggregated = AffinePoint::aggregate(&mut cs, &public_keys).unwrap();
+        assert_eq!(aggregated.get_value(), Some(expected.into_affine()));
+        let mut actual = AffinePoint::alloc(&mut cs, Some(expected.into_affine()), &params).unwrap();
+        AffinePoint::enforce_equal(&mut cs, &mut aggregated, &mut actual).unwrap();
+        assert!(cs.is_satisfied());
+
+        let no_keys: Vec<AffinePoint<Bn256, G1Affine>> = vec![];
+        assert!(AffinePoint::aggregate(&mut cs, &no_keys).is_err());
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "inconsistent with the coordinates")]
+    fn test_value_consistency_check_catches_corrupted_value() {
+        let mut cs = TrivialAssembly::<Bn256, Width4WithCustomGates, SelectorOptimizedWidth4MainGateWithDNext>::new();
+        inscribe_default_bitop_range_table(&mut cs).unwrap();
+        let params = RnsParameters::<Bn256, Fq>::new_optimal(&mut cs, 80usize);
+        let mut rng = rand::thread_rng();
+
+        let p_wit: G1Affine = rng.gen();
+        let q_wit: G1Affine = rng.gen();
+        let p = AffinePoint::alloc(&mut cs, Some(p_wit), &params).unwrap();
+        let mut q = AffinePoint::alloc(&mut cs, Some(q_wit), &params).unwrap();
+
+        // the invariant holds for the honest value
+        let _ = q.double(&mut cs).unwrap();
+        q.value = Some(p_wit);
+        let _ = q.double(&mut cs).unwrap();
+    }
+
+    #[test]
+    fn test_endomorphism_for_secp256k1() {
+        use super::super::secp256k1::fq::Fq as SecpFq;
+        use super::super::secp256k1::PointAffine as SecpG1;
+
+        let mut cs = TrivialAssembly::<Bn256, Width4WithCustomGates, SelectorOptimizedWidth4MainGateWithDNext>::new();
+        inscribe_default_bitop_range_table(&mut cs).unwrap();
+        let params = RnsParameters::<Bn256, SecpFq>::new_optimal(&mut cs, 64usize);
+        let mut rng = rand::thread_rng();
+
+        let p_wit: SecpG1 = rng.gen();
+        let expected = AffinePoint::<Bn256, SecpG1>::expected_scalar_mul(p_wit, SecpG1::endomorphism_lambda());
+        let p = AffinePoint::alloc(&mut cs, Some(p_wit), &params).unwrap();
+
+        let mut res = p.apply_endomorphism(&mut cs).unwrap();
+        assert_eq!(res.get_value(), Some(expected));
+        let mut actual = AffinePoint::alloc(&mut cs, Some(expected), &params).unwrap();
+        AffinePoint::enforce_equal(&mut cs, &mut res, &mut actual).unwrap();
+        assert!(cs.is_satisfied());
+    }
+
+    // random point on the curve y^2 = x^3 + 4: it lies in the prime order subgroup with negligible probability
+    fn random_bls12_381_g1_point_on_curve() -> crate::bellman::pairing::bls12_381::G1Affine {
+        use crate::bellman::pairing::bls12_381::{Fq as BlsFq, G1Affine as BlsG1};
+        use crate::bellman::pairing::ff::SqrtField;
+        let mut rng = rand::thread_rng();
+        loop {
+            let x: BlsFq = rng.gen();
+            let mut rhs = x;
+            rhs.square();
+            rhs.mul_assign(&x);
+            rhs.add_assign(&BlsG1::b_coeff());
+            if let Some(y) = rhs.sqrt() {
+                return BlsG1::from_xy_unchecked(x, y);
+            }
+        }
+    }
+
+    #[test]
+    fn test_mul_by_scalar_with_skipped_subgroup_check() {
+        use crate::bellman::pairing::bls12_381::{Fq as BlsFq, Fr as BlsFr, G1Affine as BlsG1};
+
+        let mut cs = TrivialAssembly::<Bn256, Width4WithCustomGates, SelectorOptimizedWidth4MainGateWithDNext>::new();
+        inscribe_default_bitop_range_table(&mut cs).unwrap();
+        let base_params = RnsParameters::<Bn256, BlsFq>::new_optimal(&mut cs, 80usize);
+        let scalar_params = RnsParameters::<Bn256, BlsFr>::new_optimal(&mut cs, 80usize);
+        let curve_params = CurveCircuitParameters::<Bn256, BlsG1>::new(base_params, scalar_params, false);
+        let mut rng = rand::thread_rng();
+
+        let p_wit: BlsG1 = rng.gen();
+        let scalar_wit: BlsFr = rng.gen();
+        let expected = AffinePoint::<Bn256, BlsG1>::expected_scalar_mul(p_wit, scalar_wit);
+
+        let mut num_gates = vec![];
+        for assume_in_subgroup in vec![false, true].into_iter() {
+            let mut p = AffinePoint::alloc(&mut cs, Some(p_wit), &curve_params.base_field_rns_params).unwrap();
+            let mut scalar = FieldElement::alloc(&mut cs, Some(scalar_wit), &curve_params.scalar_field_rns_params).unwrap();
+            let n = cs.get_current_step_number();
+            let res = p.mul_by_scalar(&mut cs, &mut scalar, &curve_params, assume_in_subgroup).unwrap();
+            num_gates.push(cs.get_current_step_number() - n);
+            assert_eq!(res.get_value(), Some(expected));
+        }
+        println!("num of gates: with subgroup check {}, without {}", num_gates[0], num_gates[1]);
+        assert!(num_gates[1] < num_gates[0]);
+        assert!(cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_generic_mul_by_scalar() {
+        use super::super::secp256k1::fq::Fq as SecpFq;
+        use super::super::secp256k1::fr::Fr as SecpFr;
+        use super::super::secp256k1::PointAffine as SecpG1;
+
+        let mut cs = TrivialAssembly::<Bn256, Width4WithCustomGates, SelectorOptimizedWidth4MainGateWithDNext>::new();
+        inscribe_default_bitop_range_table(&mut cs).unwrap();
+        let mut rng = rand::thread_rng();
+
+        // bn256 through the composite-order ladder
+        let base_params = RnsParameters::<Bn256, Fq>::new_optimal(&mut cs, 80usize);
+        let scalar_params = RnsParameters::<Bn256, Fr>::new_optimal(&mut cs, 80usize);
+        let bn_params = CurveCircuitParameters::<Bn256, G1Affine>::new(base_params, scalar_params, false);
+        let p_wit: G1Affine = rng.gen();
+        for scalar_wit in vec![rng.gen(), Fr::zero()].into_iter() {
+            let mut p = AffinePoint::alloc(&mut cs, Some(p_wit), &bn_params.base_field_rns_params).unwrap();
+            let mut scalar = FieldElement::alloc(&mut cs, Some(scalar_wit), &bn_params.scalar_field_rns_params).unwrap();
+            let res = p.mul_by_scalar(&mut cs, &mut scalar, &bn_params, true).unwrap();
+            assert_eq!(res.get_value(), Some(AffinePoint::<Bn256, G1Affine>::expected_scalar_mul(p_wit, scalar_wit)));
+            assert_eq!(res.is_infinity.get_value(), Some(scalar_wit.is_zero()));
+        }
+
+        // secp256k1 through the prime-order path
+        let base_params = RnsParameters::<Bn256, SecpFq>::new_optimal(&mut cs, 64usize);
+        let scalar_params = RnsParameters::<Bn256, SecpFr>::new_optimal(&mut cs, 80usize);
+        let secp_params = CurveCircuitParameters::<Bn256, SecpG1>::new(base_params, scalar_params, true);
+        let p_wit: SecpG1 = rng.gen();
+        for scalar_wit in vec![rng.gen(), SecpFr::zero()].into_iter() {
+            let mut p = AffinePoint::alloc(&mut cs, Some(p_wit), &secp_params.base_field_rns_params).unwrap();
+            let mut scalar = FieldElement::alloc(&mut cs, Some(scalar_wit), &secp_params.scalar_field_rns_params).unwrap();
+            let res = p.mul_by_scalar(&mut cs, &mut scalar, &secp_params, false).unwrap();
+            assert_eq!(res.get_value(), Some(AffinePoint::<Bn256, SecpG1>::expected_scalar_mul(p_wit, scalar_wit)));
+            assert_eq!(res.is_infinity.get_value(), Some(scalar_wit.is_zero()));
+        }
+
+        // secp256r1 through the prime-order path with a = -3
+        {
+            use super::super::secp256r1::fq::Fq as R1Fq;
+            use super::super::secp256r1::fr::Fr as R1Fr;
+            use super::super::secp256r1::PointAffine as R1G1;
+
+            let base_params = RnsParameters::<Bn256, R1Fq>::new_optimal(&mut cs, 64usize);
+            let scalar_params = RnsParameters::<Bn256, R1Fr>::new_optimal(&mut cs, 80usize);
+            let r1_params = CurveCircuitParameters::<Bn256, R1G1>::new(base_params, scalar_params, true);
+            let p_wit: R1G1 = rng.gen();
+            for scalar_wit in vec![rng.gen(), R1Fr::zero()].into_iter() {
+                let mut p = AffinePoint::alloc(&mut cs, Some(p_wit), &r1_params.base_field_rns_params).unwrap();
+                let mut scalar = FieldElement::alloc(&mut cs, Some(scalar_wit), &r1_params.scalar_field_rns_params).unwrap();
+                let res = p.mul_by_scalar(&mut cs, &mut scalar, &r1_params, false).unwrap();
+                assert_eq!(res.get_value(), Some(AffinePoint::<Bn256, R1G1>::expected_scalar_mul(p_wit, scalar_wit)));
+                assert_eq!(res.is_infinity.get_value(), Some(scalar_wit.is_zero()));
+            }
+        }
+
+        // the point at infinity stays there
+        let mut p = AffinePoint::alloc(&mut cs, Some(p_wit), &secp_params.base_field_rns_params).unwrap();
+        p.is_infinity = Boolean::from(AllocatedBit::alloc(&mut cs, Some(true)).unwrap());
+        p.value = Some(SecpG1::zero());
+        let mut scalar = FieldElement::alloc(&mut cs, Some(rng.gen()), &secp_params.scalar_field_rns_params).unwrap();
+        let res = p.mul_by_scalar(&mut cs, &mut scalar, &secp_params, false).unwrap();
+        assert_eq!(res.is_infinity.get_value(), Some(true));
+        assert!(cs.is_satisfied());
+    }
 
-        let mut scaled_offset = offset_generator.into_projective();
-        for _ in 0..num_doubles {
-            scaled_offset.double();
+    #[test]
+    fn test_is_valid_point() {
+        use crate::bellman::pairing::bls12_381::{Fq as BlsFq, Fr as BlsFr, G1Affine as BlsG1};
+
+        let mut cs = TrivialAssembly::<Bn256, Width4WithCustomGates, SelectorOptimizedWidth4MainGateWithDNext>::new();
+        inscribe_default_bitop_range_table(&mut cs).unwrap();
+        let base_params = RnsParameters::<Bn256, BlsFq>::new_optimal(&mut cs, 80usize);
+        let scalar_params = RnsParameters::<Bn256, BlsFr>::new_optimal(&mut cs, 80usize);
+        let curve_params = CurveCircuitParameters::<Bn256, BlsG1>::new(base_params, scalar_params, false);
+        let mut rng = rand::thread_rng();
+
+        let valid: BlsG1 = rng.gen();
+        let (x, mut y) = valid.into_xy_unchecked();
+        y.add_assign(&BlsFq::one());
+        let off_curve = BlsG1::from_xy_unchecked(x, y);
+        let not_in_subgroup = random_bls12_381_g1_point_on_curve();
+
+        for (point, expected) in vec![(valid, true), (off_curve, false), (not_in_subgroup, false)].into_iter() {
+            let mut p = AffinePoint::alloc(&mut cs, Some(point), &curve_params.base_field_rns_params).unwrap();
+            let flag = p.is_valid_point(&mut cs, &curve_params).unwrap();
+            assert_eq!(flag.get_value(), Some(expected));
         }
-        let mut offset = Self::constant(scaled_offset.into_affine(), params);
+        assert!(cs.is_satisfied());
+    }
 
-        let mut result = Self {
-            x: final_acc_x,
-            y: final_acc_y,
-            value: final_value
-        };
-        let result = result.sub_unequal(cs, &mut offset)?;
+    #[test]
+    fn test_enforce_if_in_subgroup() {
+        use crate::bellman::pairing::bls12_381::{Fq as BlsFq, Fr as BlsFr, G1Affine as BlsG1};
+
+        let p_wit = random_bls12_381_g1_point_on_curve();
+        assert!(!p_wit.mul(BlsFr::char()).into_affine().is_zero());
+        let q_wit = AffinePoint::<Bn256, BlsG1>::expected_scalar_mul(p_wit, biguint_to_fe::<BlsFr>(BlsG1::cofactor()));
+
+        // the point outside of the subgroup is rejected, while [h]P is accepted
+        for (point, should_pass) in vec![(p_wit, false), (q_wit, true)].into_iter() {
+            let mut cs = TrivialAssembly::<Bn256, Width4WithCustomGates, SelectorOptimizedWidth4MainGateWithDNext>::new();
+            inscribe_default_bitop_range_table(&mut cs).unwrap();
+            let base_params = RnsParameters::<Bn256, BlsFq>::new_optimal(&mut cs, 80usize);
+            let scalar_params = RnsParameters::<Bn256, BlsFr>::new_optimal(&mut cs, 80usize);
+            let curve_params = CurveCircuitParameters::<Bn256, BlsG1>::new(base_params, scalar_params, false);
+            let mut p = AffinePoint::alloc(&mut cs, Some(point), &curve_params.base_field_rns_params).unwrap();
+            p.enforce_if_in_subgroup(&mut cs, &curve_params).unwrap();
+            assert_eq!(cs.is_satisfied(), should_pass);
+        }
 
-        Ok(result)
+        // no constraints at all for curves of prime order
+        let mut cs = TrivialAssembly::<Bn256, Width4WithCustomGates, SelectorOptimizedWidth4MainGateWithDNext>::new();
+        inscribe_default_bitop_range_table(&mut cs).unwrap();
+        let base_params = RnsParameters::<Bn256, Fq>::new_optimal(&mut cs, 80usize);
+        let scalar_params = RnsParameters::<Bn256, Fr>::new_optimal(&mut cs, 80usize);
+        let curve_params = CurveCircuitParameters::<Bn256, G1Affine>::new(base_params, scalar_params, true);
+        let mut rng = rand::thread_rng();
+        let mut p = AffinePoint::<Bn256, G1Affine>::alloc(&mut cs, Some(rng.gen()), &curve_params.base_field_rns_params).unwrap();
+        let n = cs.get_current_step_number();
+        p.enforce_if_in_subgroup(&mut cs, &curve_params).unwrap();
+        assert_eq!(cs.get_current_step_number(), n);
+        assert!(cs.is_satisfied());
     }
-}
 
+    #[test]
+    fn test_enforce_valid_for_bls12_381() {
+        use crate::bellman::pairing::bls12_381::{Fq as BlsFq, Fr as BlsFr, G1Affine as BlsG1};
 
-impl<'a, E: Engine, G: GenericCurveAffine> AffinePoint<'a, E, G> where <G as GenericCurveAffine>::Base: PrimeField {
-    pub fn mul_by_scalar_for_prime_order_curve<CS: ConstraintSystem<E>>(
-        &mut self, cs: &mut CS, scalar: &mut FieldElement<'a, E, G::Scalar>
-    ) -> Result<ProjectivePoint<'a, E, G>, SynthesisError> {
-        let params = self.x.representation_params;
-        let scalar_decomposition = scalar.decompose_into_binary_representation(cs)?;
+        let mut cs = TrivialAssembly::<Bn256, Width4WithCustomGates, SelectorOptimizedWidth4MainGateWithDNext>::new();
+        inscribe_default_bitop_range_table(&mut cs).unwrap();
+        let base_params = RnsParameters::<Bn256, BlsFq>::new_optimal(&mut cs, 80usize);
+        let scalar_params = RnsParameters::<Bn256, BlsFr>::new_optimal(&mut cs, 80usize);
+        let curve_params = CurveCircuitParameters::<Bn256, BlsG1>::new(base_params, scalar_params, false);
+        let mut rng = rand::thread_rng();
 
-        // TODO: use standard double-add algorithm for now, optimize later
-        let mut acc = ProjectivePoint::<E, G>::zero(params);
-        let mut tmp = self.clone();
+        let p_wit: BlsG1 = rng.gen();
+        let mut p = AffinePoint::alloc(&mut cs, Some(p_wit), &curve_params.base_field_rns_params).unwrap();
+        let start = cs.get_current_step_number();
+        p.enforce_valid(&mut cs, &curve_params).unwrap();
+        let fused_gates = cs.get_current_step_number() - start;
+
+        let mut q = AffinePoint::alloc(&mut cs, Some(p_wit), &curve_params.base_field_rns_params).unwrap();
+        let start = cs.get_current_step_number();
+        AffinePoint::enforce_curve_equation(&mut cs, &q.x, &q.y).unwrap();
+        q.enforce_if_in_subgroup(&mut cs, &curve_params).unwrap();
+        let separate_gates = cs.get_current_step_number() - start;
+        assert!(fused_gates < separate_gates);
+        assert!(cs.is_satisfied());
+
+        let (x, mut y) = p_wit.into_xy_unchecked();
+        y.add_assign(&BlsFq::one());
+        let off_curve = BlsG1::from_xy_unchecked(x, y);
+        let not_in_subgroup = random_bls12_381_g1_point_on_curve();
+        for point in vec![off_curve, not_in_subgroup].into_iter() {
+            let mut cs = TrivialAssembly::<Bn256, Width4WithCustomGates, SelectorOptimizedWidth4MainGateWithDNext>::new();
+            inscribe_default_bitop_range_table(&mut cs).unwrap();
+            let base_params = RnsParameters::<Bn256, BlsFq>::new_optimal(&mut cs, 80usize);
+            let scalar_params = RnsParameters::<Bn256, BlsFr>::new_optimal(&mut cs, 80usize);
+            let curve_params = CurveCircuitParameters::<Bn256, BlsG1>::new(base_params, scalar_params, false);
+            let mut p = AffinePoint::alloc(&mut cs, Some(point), &curve_params.base_field_rns_params).unwrap();
+            p.enforce_valid(&mut cs, &curve_params).unwrap();
+            assert!(!cs.is_satisfied());
+        }
+    }
 
-        for bit in scalar_decomposition.into_iter() {
-            let added = acc.add_mixed(cs, &mut tmp)?;
-            acc = ProjectivePoint::conditionally_select(cs, &bit, &added, &acc)?;
-            tmp = tmp.double(cs)?;
+    #[test]
+    fn test_clear_cofactor_for_bls12_381() {
+        use crate::bellman::pairing::bls12_381::{Fq as BlsFq, Fr as BlsFr, G1Affine as BlsG1};
+
+        let mut cs = TrivialAssembly::<Bn256, Width4WithCustomGates, SelectorOptimizedWidth4MainGateWithDNext>::new();
+        inscribe_default_bitop_range_table(&mut cs).unwrap();
+        let params = RnsParameters::<Bn256, BlsFq>::new_optimal(&mut cs, 80usize);
+
+        let p_wit = random_bls12_381_g1_point_on_curve();
+        let subgroup_order = repr_to_biguint::<BlsFr>(&BlsFr::char());
+        assert!(!p_wit.mul(BlsFr::char()).into_affine().is_zero());
+
+        let mut p = AffinePoint::alloc(&mut cs, Some(p_wit), &params).unwrap();
+        let mut res = p.clear_cofactor(&mut cs).unwrap();
+        let expected = AffinePoint::<Bn256, BlsG1>::expected_scalar_mul(
+            p_wit, biguint_to_fe::<BlsFr>(BlsG1::cofactor())
+        );
+        assert_eq!(res.get_value(), Some(expected));
+        assert!(expected.mul(BlsFr::char()).into_affine().is_zero());
+
+        res.enforce_order_divides(&mut cs, &subgroup_order).unwrap();
+        assert!(cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_identity_is_neutral_for_complete_addition() {
+        let mut cs = TrivialAssembly::<Bn256, Width4WithCustomGates, SelectorOptimizedWidth4MainGateWithDNext>::new();
+        inscribe_default_bitop_range_table(&mut cs).unwrap();
+        let params = RnsParameters::<Bn256, Fq>::new_optimal(&mut cs, 80usize);
+        let mut rng = rand::thread_rng();
+
+        let p_wit: G1Affine = rng.gen();
+        let mut p = AffinePoint::alloc(&mut cs, Some(p_wit), &params).unwrap();
+        let mut identity = AffinePointWithInfinity::<Bn256, G1Affine>::identity(&params);
+        assert_eq!(identity.is_infinity.get_value(), Some(true));
+        assert!(identity.is_infinity.is_constant());
+        assert_eq!(identity.get_value(), Some(G1Affine::zero()));
+
+        let mut res = identity.add_complete(&mut cs, &mut p).unwrap();
+        assert_eq!(res.is_infinity.get_value(), Some(false));
+        assert_eq!(res.get_value(), Some(p_wit));
+        AffinePoint::enforce_equal(&mut cs, &mut res, &mut p).unwrap();
+        assert!(cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_add_for_degenerate_cases() {
+        let mut cs = TrivialAssembly::<Bn256, Width4WithCustomGates, SelectorOptimizedWidth4MainGateWithDNext>::new();
+        inscribe_default_bitop_range_table(&mut cs).unwrap();
+        let params = RnsParameters::<Bn256, Fq>::new_optimal(&mut cs, 80usize);
+        let mut rng = rand::thread_rng();
+
+        let p_wit: G1Affine = rng.gen();
+        let mut p = AffinePoint::alloc(&mut cs, Some(p_wit), &params).unwrap();
+        p.is_infinity = Boolean::from(AllocatedBit::alloc(&mut cs, Some(false)).unwrap());
+        let p_copy = p.clone();
+        let mut minus_p = p.negate(&mut cs).unwrap();
+        // the point at infinity with variable flag and meaningless coordinates
+        let inf = p.add(&mut cs, &mut minus_p).unwrap();
+        assert_eq!(inf.get_value(), Some(G1Affine::zero()));
+        let constant_inf = AffinePoint::zero(&params);
+
+        let mut cases = vec![
+            (p.clone(), p_copy.clone()), (p.clone(), minus_p.clone()), (p.clone(), inf.clone()), 
+            (inf.clone(), p.clone()), (inf.clone(), inf.clone()), (constant_inf.clone(), p.clone()),
+            (p.clone(), constant_inf.clone())
+        ];
+        for _ in 0..4 {
+            let q_wit: G1Affine = rng.gen();
+            let mut q = AffinePoint::alloc(&mut cs, Some(q_wit), &params).unwrap();
+            q.is_infinity = Boolean::from(AllocatedBit::alloc(&mut cs, Some(false)).unwrap());
+            cases.push((p.clone(), q));
         }
-        
-        Ok(acc)
+
+        let mut num_gates = vec![];
+        for (mut a, mut b) in cases.into_iter() {
+            let expected = {
+                let mut tmp = a.get_value().unwrap().into_projective();
+                tmp.add_assign_mixed(&b.get_value().unwrap());
+                tmp.into_affine()
+            };
+            let n = cs.get_current_step_number();
+            let mut res = a.add(&mut cs, &mut b).unwrap();
+            num_gates.push(cs.get_current_step_number() - n);
+            assert_eq!(res.get_value(), Some(expected));
+            assert_eq!(res.is_infinity.get_value(), Some(expected.is_zero()));
+            if !expected.is_zero() {
+                let mut actual = AffinePoint::alloc(&mut cs, Some(expected), &params).unwrap();
+                AffinePoint::enforce_equal(&mut cs, &mut res, &mut actual).unwrap();
+            }
+        }
+        println!("num of gates for every case: {:?}", num_gates);
+        // additions with the point which is known to be at infinity are free
+        assert_eq!(num_gates[5], 0);
+        assert_eq!(num_gates[6], 0);
+        assert!(cs.is_satisfied());
     }
-}
 
+    #[test]
+    fn test_add_complete_has_uniform_cost() {
+        let mut cs = TrivialAssembly::<Bn256, Width4WithCustomGates, SelectorOptimizedWidth4MainGateWithDNext>::new();
+        inscribe_default_bitop_range_table(&mut cs).unwrap();
+        let params = RnsParameters::<Bn256, Fq>::new_optimal(&mut cs, 80usize);
+        let mut rng = rand::thread_rng();
 
-#[cfg(test)]
-mod test {
-    use super::*;
-    use crate::bellman::pairing::bn256::{Fq, Bn256, Fr, G1Affine};
-    use plonk::circuit::Width4WithCustomGates;
-    use bellman::plonk::better_better_cs::gates::{selector_optimized_with_d_next::SelectorOptimizedWidth4MainGateWithDNext, self};
-    use rand::{XorShiftRng, SeedableRng, Rng};
-    use bellman::plonk::better_better_cs::cs::*;
+        let p_wit: G1Affine = rng.gen();
+        let q_wit: G1Affine = rng.gen();
+        let mut p = AffinePoint::alloc(&mut cs, Some(p_wit), &params).unwrap();
+        let p_copy = AffinePoint::alloc(&mut cs, Some(p_wit), &params).unwrap();
+        let minus_p = p.negate(&mut cs).unwrap();
+        let q = AffinePoint::alloc(&mut cs, Some(q_wit), &params).unwrap();
+
+        // P + P, P + (-P) and P + Q: the same constraints are generated in all the cases
+        let mut num_gates = vec![];
+        let mut results = vec![];
+        for other in vec![p_copy, minus_p, q].into_iter() {
+            let n = cs.get_current_step_number();
+            results.push(p.add_complete(&mut cs, &mut other.clone()).unwrap());
+            num_gates.push(cs.get_current_step_number() - n);
+        }
+        println!("num of gates for P + P, P + (-P), P + Q: {:?}", num_gates);
+        assert!(num_gates.iter().all(|n| *n == num_gates[0]));
+
+        let mut doubled = p_wit.into_projective();
+        doubled.double();
+        let mut sum = p_wit.into_projective();
+        sum.add_assign_mixed(&q_wit);
+        assert_eq!(results[0].get_value(), Some(doubled.into_affine()));
+        assert_eq!(results[1].is_infinity.get_value(), Some(true));
+        assert_eq!(results[2].get_value(), Some(sum.into_affine()));
+        assert!(cs.is_satisfied());
+    }
 
     #[test]
-    fn test_arithmetic_for_bn256_curve() {
+    fn test_select_from_table_for_4_bit_window() {
+        let mut cs = TrivialAssembly::<Bn256, Width4WithCustomGates, SelectorOptimizedWidth4MainGateWithDNext>::new();
+        inscribe_default_bitop_range_table(&mut cs).unwrap();
+        let params = RnsParameters::<Bn256, Fq>::new_optimal(&mut cs, 80usize);
+        let mut rng = rand::thread_rng();
+
+        const WINDOW: usize = 4;
+        let table_wit: Vec<G1Affine> = (0..1 << WINDOW).map(|_| rng.gen()).collect();
+        let table: Vec<_> = table_wit.iter().map(|p| AffinePoint::alloc(&mut cs, Some(*p), &params).unwrap()).collect();
+        let index: usize = rng.gen_range(0, 1 << WINDOW);
+        let bits: Vec<_> = (0..WINDOW).map(|i| {
+            Boolean::from(AllocatedBit::alloc(&mut cs, Some((index >> i) & 1 == 1)).unwrap())
+        }).collect();
+
+        let n = cs.get_current_step_number();
+        let selected = AffinePoint::select_from_table(&mut cs, &bits, &table).unwrap();
+        let table_select_gates = cs.get_current_step_number() - n;
+
+        let n = cs.get_current_step_number();
+        let mut layer = table.clone();
+        for bit in bits.iter() {
+            layer = layer.chunks(2).map(|pair| AffinePoint::select(&mut cs, bit, &pair[1], &pair[0]).unwrap()).collect();
+        }
+        let naive = layer.pop().unwrap();
+        let naive_select_gates = cs.get_current_step_number() - n;
+
+        println!("num of gates for selection from 16 points: {} (naive nested select: {})", table_select_gates, naive_select_gates);
+        assert!(table_select_gates < naive_select_gates);
+        assert_eq!(selected.get_value(), Some(table_wit[index]));
+        assert_eq!(naive.get_value(), Some(table_wit[index]));
+        assert!(cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_conditionally_replace() {
+        let mut cs = TrivialAssembly::<Bn256, Width4WithCustomGates, SelectorOptimizedWidth4MainGateWithDNext>::new();
+        inscribe_default_bitop_range_table(&mut cs).unwrap();
+        let params = RnsParameters::<Bn256, Fq>::new_optimal(&mut cs, 80usize);
+        let mut rng = rand::thread_rng();
+
+        let p_wit: G1Affine = rng.gen();
+        let q_wit: G1Affine = rng.gen();
+        let mut p = AffinePoint::alloc(&mut cs, Some(p_wit), &params).unwrap();
+        let mut q = AffinePoint::alloc(&mut cs, Some(q_wit), &params).unwrap();
+        let mut acc = p.clone();
+
+        let off = Boolean::from(AllocatedBit::alloc(&mut cs, Some(false)).unwrap());
+        acc.conditionally_replace(&mut cs, &off, &q).unwrap();
+        assert_eq!(acc.get_value(), Some(p_wit));
+        AffinePoint::enforce_equal(&mut cs, &mut acc, &mut p).unwrap();
+
+        let on = Boolean::from(AllocatedBit::alloc(&mut cs, Some(true)).unwrap());
+        acc.conditionally_replace(&mut cs, &on, &q).unwrap();
+        assert_eq!(acc.get_value(), Some(q_wit));
+        AffinePoint::enforce_equal(&mut cs, &mut acc, &mut q).unwrap();
+
+        // constant flags take the zero-cost path
+        let n = cs.get_current_step_number();
+        acc.conditionally_replace(&mut cs, &Boolean::constant(true), &p).unwrap();
+        acc.conditionally_replace(&mut cs, &Boolean::constant(false), &q).unwrap();
+        assert_eq!(cs.get_current_step_number(), n);
+        assert_eq!(acc.get_value(), Some(p_wit));
+        assert!(cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_arithmetic_with_point_at_infinity() {
+        let mut cs = TrivialAssembly::<Bn256, Width4WithCustomGates, SelectorOptimizedWidth4MainGateWithDNext>::new();
+        inscribe_default_bitop_range_table(&mut cs).unwrap();
+        let params = RnsParameters::<Bn256, Fq>::new_optimal(&mut cs, 80usize);
+        let mut rng = rand::thread_rng();
+
+        let p_wit: G1Affine = rng.gen();
+        let mut p = AffinePoint::alloc(&mut cs, Some(p_wit), &params).unwrap();
+
+        // the point at infinity with constant flag is resolved without any gates
+        let mut zero = AffinePoint::zero(&params);
+        let mut res = p.add_unequal(&mut cs, &mut zero).unwrap();
+        AffinePoint::enforce_equal(&mut cs, &mut res, &mut p).unwrap();
+        let mut res = zero.add_unequal(&mut cs, &mut p).unwrap();
+        AffinePoint::enforce_equal(&mut cs, &mut res, &mut p).unwrap();
+
+        // the point at infinity with allocated flag goes through complete addition law 
+        let mut allocated_zero = AffinePoint::zero(&params);
+        allocated_zero.is_infinity = Boolean::from(AllocatedBit::alloc(&mut cs, Some(true)).unwrap());
+        let mut res = p.add_unequal(&mut cs, &mut allocated_zero).unwrap();
+        assert_eq!(res.is_infinity.get_value(), Some(false));
+        AffinePoint::enforce_equal(&mut cs, &mut res, &mut p).unwrap();
+        let mut res = allocated_zero.add_unequal(&mut cs, &mut p).unwrap();
+        assert_eq!(res.is_infinity.get_value(), Some(false));
+        AffinePoint::enforce_equal(&mut cs, &mut res, &mut p).unwrap();
+        let doubled_zero = allocated_zero.double(&mut cs).unwrap();
+        assert_eq!(doubled_zero.is_infinity.get_value(), Some(true));
+
+        // P + (-P) = O
+        let mut p_with_flag = p.clone();
+        p_with_flag.is_infinity = Boolean::from(AllocatedBit::alloc(&mut cs, Some(false)).unwrap());
+        let mut minus_p = p.negate(&mut cs).unwrap();
+        let res = p_with_flag.add_unequal(&mut cs, &mut minus_p).unwrap();
+        assert_eq!(res.is_infinity.get_value(), Some(true));
+        assert!(cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_fixed_window_strategy() {
+        let mut cs = TrivialAssembly::<Bn256, Width4WithCustomGates, SelectorOptimizedWidth4MainGateWithDNext>::new();
+        inscribe_default_bitop_range_table(&mut cs).unwrap();
+        let params = RnsParameters::<Bn256, Fq>::new_optimal(&mut cs, 80usize);
+        let scalar_params = RnsParameters::<Bn256, Fr>::new_optimal(&mut cs, 80usize);
+        let mut rng = rand::thread_rng();
+
+        // 4 does not divide the bitlength of Fr, so the top window is shorter;
+        // small scalar has a lot of leading zero windows
+        let scalars: Vec<Fr> = vec![rng.gen(), rng.gen(), Fr::from_str("5").unwrap()];
+        for scalar_wit in scalars.into_iter() {
+            let a: G1Affine = rng.gen();
+            let mut a = AffinePoint::alloc(&mut cs, Some(a), &params).unwrap();
+            let mut scalar = FieldElement::alloc(&mut cs, Some(scalar_wit), &scalar_params).unwrap();
+
+            let naive_start = cs.get_current_step_number();
+            let naive = a.mul_by_scalar_for_prime_order_curve_with_strategy(
+                &mut cs, &mut scalar, PointByScalarMulStrategy::Basic
+            ).unwrap();
+            let naive_end = cs.get_current_step_number();
+            let windowed = a.mul_by_scalar_for_prime_order_curve_with_strategy(
+                &mut cs, &mut scalar, PointByScalarMulStrategy::FixedWindow(4)
+            ).unwrap();
+            let windowed_end = cs.get_current_step_number();
+            println!(
+                "num of gates: naive {}, fixed window {}, delta {}", naive_end - naive_start, windowed_end - naive_end, 
+                (naive_end - naive_start) as i64 - (windowed_end - naive_end) as i64
+            );
+
+            let mut naive = unsafe { naive.convert_to_affine(&mut cs).unwrap() };
+            let mut windowed = unsafe { windowed.convert_to_affine(&mut cs).unwrap() };
+            assert_eq!(naive.get_value(), windowed.get_value());
+            AffinePoint::enforce_equal(&mut cs, &mut naive, &mut windowed).unwrap();
+        }
+        assert!(cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_montgomery_ladder_strategy() {
+        let mut cs = TrivialAssembly::<Bn256, Width4WithCustomGates, SelectorOptimizedWidth4MainGateWithDNext>::new();
+        inscribe_default_bitop_range_table(&mut cs).unwrap();
+        let params = RnsParameters::<Bn256, Fq>::new_optimal(&mut cs, 80usize);
+        let scalar_params = RnsParameters::<Bn256, Fr>::new_optimal(&mut cs, 80usize);
+        let mut rng = rand::thread_rng();
+
+        let scalars: Vec<Fr> = vec![rng.gen(), rng.gen(), Fr::one()];
+        for scalar_wit in scalars.into_iter() {
+            let a: G1Affine = rng.gen();
+            let mut a = AffinePoint::alloc(&mut cs, Some(a), &params).unwrap();
+            let mut scalar = FieldElement::alloc(&mut cs, Some(scalar_wit), &scalar_params).unwrap();
+
+            let naive_start = cs.get_current_step_number();
+            let naive = a.mul_by_scalar_for_prime_order_curve(&mut cs, &mut scalar).unwrap();
+            let naive_end = cs.get_current_step_number();
+            let mut ladder = a.mul_by_scalar_montgomery(&mut cs, &mut scalar).unwrap();
+            let ladder_end = cs.get_current_step_number();
+            println!("num of gates: naive {}, montgomery ladder {}", naive_end - naive_start, ladder_end - naive_end);
+
+            let mut naive = unsafe { naive.convert_to_affine(&mut cs).unwrap() };
+            assert_eq!(naive.get_value(), ladder.get_value());
+            AffinePoint::enforce_equal(&mut cs, &mut naive, &mut ladder).unwrap();
+
+            let via_strategy = a.mul_by_scalar_for_prime_order_curve_with_strategy(
+                &mut cs, &mut scalar, PointByScalarMulStrategy::MontgomeryLadder
+            ).unwrap();
+            assert_eq!(via_strategy.get_value(), naive.get_value());
+        }
+
+        // zero scalar yields the point at infinity
+        let a: G1Affine = rng.gen();
+        let mut a = AffinePoint::alloc(&mut cs, Some(a), &params).unwrap();
+        let mut zero = FieldElement::alloc(&mut cs, Some(Fr::zero()), &scalar_params).unwrap();
+        let res = a.mul_by_scalar_montgomery(&mut cs, &mut zero).unwrap();
+        assert_eq!(res.is_infinity.get_value(), Some(true));
+        assert_eq!(res.get_value(), Some(G1Affine::zero()));
+        let via_strategy = a.mul_by_scalar_for_prime_order_curve_with_strategy(
+            &mut cs, &mut zero, PointByScalarMulStrategy::MontgomeryLadder
+        ).unwrap();
+        assert!(via_strategy.z.get_field_value().unwrap().is_zero());
+        assert!(cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_montgomery_ladder_is_cheaper_than_basic_for_secp256k1() {
+        use super::super::secp256k1::fq::Fq as SecpFq;
+        use super::super::secp256k1::fr::Fr as SecpFr;
+        use super::super::secp256k1::PointAffine as SecpG1;
+
+        let mut cs = TrivialAssembly::<Bn256, Width4WithCustomGates, SelectorOptimizedWidth4MainGateWithDNext>::new();
+        inscribe_default_bitop_range_table(&mut cs).unwrap();
+        let params = RnsParameters::<Bn256, SecpFq>::new_optimal(&mut cs, 64usize);
+        let scalar_params = RnsParameters::<Bn256, SecpFr>::new_optimal(&mut cs, 64usize);
+        let mut rng = rand::thread_rng();
+
+        let a_wit: SecpG1 = rng.gen();
+        let scalar_wit: SecpFr = rng.gen();
+        let mut a = AffinePoint::alloc(&mut cs, Some(a_wit), &params).unwrap();
+        let mut scalar = FieldElement::alloc(&mut cs, Some(scalar_wit), &scalar_params).unwrap();
+
+        let basic_start = cs.get_current_step_number();
+        let mut basic = a.mul_by_scalar_for_prime_order_curve_with_strategy(
+            &mut cs, &mut scalar, PointByScalarMulStrategy::Basic
+        ).unwrap();
+        let basic_gates = cs.get_current_step_number() - basic_start;
+
+        let ladder_start = cs.get_current_step_number();
+        let mut ladder = a.mul_by_scalar_for_prime_order_curve_with_strategy(
+            &mut cs, &mut scalar, PointByScalarMulStrategy::MontgomeryLadder
+        ).unwrap();
+        let ladder_gates = cs.get_current_step_number() - ladder_start;
+        assert!(ladder_gates < basic_gates);
+
+        let mut basic_affine = basic.convert_to_affine_checked(&mut cs).unwrap();
+        let mut ladder_affine = ladder.convert_to_affine_checked(&mut cs).unwrap();
+        let expected = AffinePoint::<Bn256, SecpG1>::expected_scalar_mul(a_wit, scalar_wit);
+        assert_eq!(ladder_affine.get_value(), Some(expected));
+        AffinePoint::enforce_equal(&mut cs, &mut basic_affine, &mut ladder_affine).unwrap();
+        assert!(cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_mul_by_small_scalar_with_fixed_window() {
         let mut cs = TrivialAssembly::<Bn256, Width4WithCustomGates, SelectorOptimizedWidth4MainGateWithDNext>::new();
         inscribe_default_bitop_range_table(&mut cs).unwrap();
         let params = RnsParameters::<Bn256, Fq>::new_optimal(&mut cs, 80usize);
@@ -586,62 +3741,343 @@ mod test {
         let mut rng = rand::thread_rng();
 
         let a: G1Affine = rng.gen();
-        let scalar : Fr = rng.gen();
+        let scalar_wit = Fr::from_str("1000003").unwrap();
+        let mut a = AffinePoint::alloc(&mut cs, Some(a), &params).unwrap();
+        let mut scalar = FieldElement::alloc(&mut cs, Some(scalar_wit), &scalar_params).unwrap();
+
+        let full_start = cs.get_current_step_number();
+        let full = a.mul_by_scalar_for_prime_order_curve_with_strategy(
+            &mut cs, &mut scalar, PointByScalarMulStrategy::FixedWindow(4)
+        ).unwrap();
+        let full_end = cs.get_current_step_number();
+        let short = a.mul_by_small_scalar_with_fixed_window(&mut cs, &mut scalar, 4, 32).unwrap();
+        let short_end = cs.get_current_step_number();
+        println!("num of gates: full {}, with skipped high windows {}", full_end - full_start, short_end - full_end);
+        assert!(short_end - full_end < full_end - full_start);
+
+        let mut full = unsafe { full.convert_to_affine(&mut cs).unwrap() };
+        let mut short = unsafe { short.convert_to_affine(&mut cs).unwrap() };
+        assert_eq!(full.get_value(), short.get_value());
+        AffinePoint::enforce_equal(&mut cs, &mut full, &mut short).unwrap();
+        assert!(cs.is_satisfied());
+
+        // the scalar which doesn't fit into the declared bitlength is rejected
+        let mut cs = TrivialAssembly::<Bn256, Width4WithCustomGates, SelectorOptimizedWidth4MainGateWithDNext>::new();
+        inscribe_default_bitop_range_table(&mut cs).unwrap();
+        let params = RnsParameters::<Bn256, Fq>::new_optimal(&mut cs, 80usize);
+        let scalar_params = RnsParameters::<Bn256, Fr>::new_optimal(&mut cs, 80usize);
+        let mut a = AffinePoint::<Bn256, G1Affine>::alloc(&mut cs, Some(rng.gen()), &params).unwrap();
+        let mut scalar = FieldElement::alloc(&mut cs, Some(scalar_wit), &scalar_params).unwrap();
+        let _ = a.mul_by_small_scalar_with_fixed_window(&mut cs, &mut scalar, 4, 16).unwrap();
+        assert!(!cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_arithmetic_for_secp256r1_curve() {
+        use super::super::secp256r1::fq::Fq as SecpFq;
+        use super::super::secp256r1::fr::Fr as SecpFr;
+        use super::super::secp256r1::PointAffine as SecpG1;
+
+        let mut cs = TrivialAssembly::<Bn256, Width4WithCustomGates, SelectorOptimizedWidth4MainGateWithDNext>::new();
+        inscribe_default_bitop_range_table(&mut cs).unwrap();
+        let params = RnsParameters::<Bn256, SecpFq>::new_optimal(&mut cs, 64usize);
+        let scalar_params = RnsParameters::<Bn256, SecpFr>::new_optimal(&mut cs, 80usize);
+        let mut rng = rand::thread_rng();
+        assert!(!SecpG1::a_coeff().is_zero());
+
+        let a: SecpG1 = rng.gen();
+        let mut doubled = a.into_projective();
+        doubled.double();
+        let doubled = doubled.into_affine();
+        let scalar : SecpFr = rng.gen();
         let mut tmp = a.into_projective();
         tmp.mul_assign(scalar);
         let result = tmp.into_affine();
         
         let mut a = AffinePoint::alloc(&mut cs, Some(a), &params).unwrap();
         let mut scalar = FieldElement::alloc(&mut cs, Some(scalar), &scalar_params).unwrap();
+        let mut actual_doubled = AffinePoint::alloc(&mut cs, Some(doubled), &params).unwrap();
         let mut actual_result = AffinePoint::alloc(&mut cs, Some(result), &params).unwrap();
+
+        // the tangent includes the coefficient a = -3
+        let mut computed_doubled = a.double(&mut cs).unwrap();
+        AffinePoint::enforce_equal(&mut cs, &mut computed_doubled, &mut actual_doubled).unwrap();
+
         let naive_mul_start = cs.get_current_step_number();
-        let mut result = a.mul_by_scalar_for_prime_order_curve(&mut cs, &mut scalar).unwrap();
-        let mut result = unsafe { result.convert_to_affine(&mut cs).unwrap() };
+        let mut result = a.mul_by_scalar_for_composite_order_curve(&mut cs, &mut scalar).unwrap();
         let naive_mul_end = cs.get_current_step_number();
         println!("num of gates: {}", naive_mul_end - naive_mul_start);
 
-        // println!("actual result: x: {}, y: {}", actual_result.x.get_field_value().unwrap(), actual_result.y.get_field_value().unwrap());
-        // println!("computed result: x: {}, y: {}", result.x.get_field_value().unwrap(), result.y.get_field_value().unwrap());
+        AffinePoint::enforce_equal(&mut cs, &mut result, &mut actual_result).unwrap();
 
+        // the curve is of prime order: projective paths go through the formulas for general a
+        let strategies = vec![
+            PointByScalarMulStrategy::Basic, 
+            PointByScalarMulStrategy::FixedWindow(4), 
+            PointByScalarMulStrategy::MontgomeryLadder
+        ];
+        for strategy in strategies.into_iter() {
+            let mut result = a.mul_by_scalar_for_prime_order_curve_with_strategy(&mut cs, &mut scalar, strategy).unwrap();
+            let mut result = result.convert_to_affine_checked(&mut cs).unwrap();
+            AffinePoint::enforce_equal(&mut cs, &mut result, &mut actual_result).unwrap();
+        }
+        let mut result = a.windowed_mul(&mut cs, &mut scalar, SecpFr::NUM_BITS as usize).unwrap();
+        let mut result = result.convert_to_affine_checked(&mut cs).unwrap();
         AffinePoint::enforce_equal(&mut cs, &mut result, &mut actual_result).unwrap();
+
+        let mut scalars = vec![scalar.clone(), scalar];
+        let mut points = vec![a.clone(), a.negate(&mut cs).unwrap()];
+        let mut sum = AffinePoint::multiexp(&mut cs, &mut scalars, &mut points).unwrap();
+        let is_zero = sum.z.is_zero(&mut cs).unwrap();
+        assert_eq!(is_zero.get_value(), Some(true));
         assert!(cs.is_satisfied()); 
-        println!("SCALAR MULTIPLICATION final");
     }
 
     #[test]
-    fn test_arithmetic_for_secp256k1_curve() {
-        use super::super::secp256k1::fq::Fq as SecpFq;
-        use super::super::secp256k1::fr::Fr as SecpFr;
-        use super::super::secp256k1::PointAffine as SecpG1;
+    fn test_complete_subtraction_for_bn256_curve() {
+        let mut cs = TrivialAssembly::<Bn256, Width4WithCustomGates, SelectorOptimizedWidth4MainGateWithDNext>::new();
+        inscribe_default_bitop_range_table(&mut cs).unwrap();
+        let params = RnsParameters::<Bn256, Fq>::new_optimal(&mut cs, 80usize);
+        let mut rng = rand::thread_rng();
+
+        let p_wit: G1Affine = rng.gen();
+        let q_wit: G1Affine = rng.gen();
+        let mut p_minus_q_wit = p_wit.into_projective();
+        let mut tmp = q_wit;
+        tmp.negate();
+        p_minus_q_wit.add_assign_mixed(&tmp);
+        let mut minus_p_wit = p_wit;
+        minus_p_wit.negate();
+
+        let mut p = AffinePoint::alloc(&mut cs, Some(p_wit), &params).unwrap();
+        let mut p_copy = AffinePoint::alloc(&mut cs, Some(p_wit), &params).unwrap();
+        let mut q = AffinePoint::alloc(&mut cs, Some(q_wit), &params).unwrap();
+        let mut zero = AffinePoint::zero(&params);
+        let mut expected_p_minus_q = AffinePoint::alloc(&mut cs, Some(p_minus_q_wit.into_affine()), &params).unwrap();
+        let mut expected_minus_p = AffinePoint::alloc(&mut cs, Some(minus_p_wit), &params).unwrap();
+
+        // P - P = O
+        let res = p.sub_complete(&mut cs, &mut p_copy).unwrap();
+        assert_eq!(res.is_infinity.get_value(), Some(true));
+        assert!(res.get_value().unwrap().is_zero());
+
+        // P - O = P
+        let mut res = p.sub_complete(&mut cs, &mut zero).unwrap();
+        assert_eq!(res.is_infinity.get_value(), Some(false));
+        AffinePoint::enforce_equal(&mut cs, &mut res, &mut p_copy).unwrap();
+
+        // O - P = -P
+        let mut res = zero.sub_complete(&mut cs, &mut p).unwrap();
+        assert_eq!(res.is_infinity.get_value(), Some(false));
+        AffinePoint::enforce_equal(&mut cs, &mut res, &mut expected_minus_p).unwrap();
+
+        // P - Q
+        let counter_start = cs.get_current_step_number();
+        let mut res = p.sub_complete(&mut cs, &mut q).unwrap();
+        println!("complete subtraction: {} gates", cs.get_current_step_number() - counter_start);
+        assert_eq!(res.is_infinity.get_value(), Some(false));
+        AffinePoint::enforce_equal(&mut cs, &mut res, &mut expected_p_minus_q).unwrap();
+
+        assert!(cs.is_satisfied());
+    }
 
+    #[test]
+    fn test_deterministic_point_from_seed() {
         let mut cs = TrivialAssembly::<Bn256, Width4WithCustomGates, SelectorOptimizedWidth4MainGateWithDNext>::new();
         inscribe_default_bitop_range_table(&mut cs).unwrap();
-        let params = RnsParameters::<Bn256, SecpFq>::new_optimal(&mut cs, 64usize);
-        let scalar_params = RnsParameters::<Bn256, SecpFr>::new_optimal(&mut cs, 80usize);
+        let params = RnsParameters::<Bn256, Fq>::new_optimal(&mut cs, 80usize);
+
+        let a = AffinePoint::<Bn256, G1Affine>::deterministic_from_seed(&mut cs, 42, &params).unwrap();
+        let b = AffinePoint::<Bn256, G1Affine>::deterministic_from_seed(&mut cs, 42, &params).unwrap();
+        let c = AffinePoint::<Bn256, G1Affine>::deterministic_from_seed(&mut cs, 43, &params).unwrap();
+        assert_eq!(a.get_value().unwrap(), b.get_value().unwrap());
+        assert!(a.get_value().unwrap() != c.get_value().unwrap());
+        assert!(cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_enforce_addition() {
         let mut rng = rand::thread_rng();
+        let p_wit: G1Affine = rng.gen();
+        let q_wit: G1Affine = rng.gen();
+        let mut r_wit = p_wit.into_projective();
+        r_wit.add_assign_mixed(&q_wit);
+        let r_wit = r_wit.into_affine();
+        let mut wrong_r_wit = r_wit.into_projective();
+        wrong_r_wit.add_assign_mixed(&G1Affine::one());
+        let wrong_r_wit = wrong_r_wit.into_affine();
+
+        for (r_wit, is_valid) in vec![(r_wit, true), (wrong_r_wit, false)].into_iter() {
+            let mut cs = TrivialAssembly::<Bn256, Width4WithCustomGates, SelectorOptimizedWidth4MainGateWithDNext>::new();
+            inscribe_default_bitop_range_table(&mut cs).unwrap();
+            let params = RnsParameters::<Bn256, Fq>::new_optimal(&mut cs, 80usize);
+
+            let mut p = AffinePoint::alloc(&mut cs, Some(p_wit), &params).unwrap();
+            let mut q = AffinePoint::alloc(&mut cs, Some(q_wit), &params).unwrap();
+            let r = AffinePoint::alloc(&mut cs, Some(r_wit), &params).unwrap();
+            let counter_start = cs.get_current_step_number();
+            AffinePoint::enforce_addition(&mut cs, &mut p, &mut q, &r).unwrap();
+            println!("enforce addition: {} gates", cs.get_current_step_number() - counter_start);
+            assert_eq!(cs.is_satisfied(), is_valid);
+        }
+    }
 
-        let a: SecpG1 = rng.gen();
-        let scalar : SecpFr = rng.gen();
-        let mut tmp = a.into_projective();
-        tmp.mul_assign(scalar);
+    #[test]
+    fn test_fixed_and_variable_mul() {
+        let mut cs = TrivialAssembly::<Bn256, Width4WithCustomGates, SelectorOptimizedWidth4MainGateWithDNext>::new();
+        inscribe_default_bitop_range_table(&mut cs).unwrap();
+        let params = RnsParameters::<Bn256, Fq>::new_optimal(&mut cs, 80usize);
+        let scalar_params = RnsParameters::<Bn256, Fr>::new_optimal(&mut cs, 80usize);
+        let mut rng = rand::thread_rng();
+
+        let generator = G1Affine::one();
+        let q_wit: G1Affine = rng.gen();
+        let u1_wit: Fr = rng.gen();
+        let u2_wit: Fr = rng.gen();
+        let mut result_wit = generator.mul(u1_wit.into_repr());
+        result_wit.add_assign(&q_wit.mul(u2_wit.into_repr()));
+
+        let mut q = AffinePoint::alloc(&mut cs, Some(q_wit), &params).unwrap();
+        let mut u1 = FieldElement::alloc(&mut cs, Some(u1_wit), &scalar_params).unwrap();
+        let mut u2 = FieldElement::alloc(&mut cs, Some(u2_wit), &scalar_params).unwrap();
+        let mut expected = AffinePoint::alloc(&mut cs, Some(result_wit.into_affine()), &params).unwrap();
+
+        let counter_start = cs.get_current_step_number();
+        let result = AffinePoint::fixed_and_variable_mul(&mut cs, generator, &mut u1, &mut q, &mut u2).unwrap();
+        let fixed_and_variable_gates = cs.get_current_step_number() - counter_start;
+        let mut result = unsafe { result.convert_to_affine(&mut cs).unwrap() };
+
+        AffinePoint::enforce_equal(&mut cs, &mut result, &mut expected).unwrap();
+        assert!(cs.is_satisfied());
+
+        // the same sum computed by two generic multiplications
+        let counter_start = cs.get_current_step_number();
+        let mut g = AffinePoint::constant(generator, &params);
+        let fixed_part = g.mul_by_scalar_for_prime_order_curve(&mut cs, &mut u1).unwrap();
+        let variable_part = q.mul_by_scalar_for_prime_order_curve(&mut cs, &mut u2).unwrap();
+        let _ = fixed_part.add(&mut cs, &variable_part).unwrap();
+        let generic_gates = cs.get_current_step_number() - counter_start;
+        assert!(fixed_and_variable_gates < generic_gates);
+    }
+
+    #[test]
+    fn test_auto_reduce_policy() {
+        let mut rng = rand::thread_rng();
+        let a_wit: G1Affine = rng.gen();
+        let b_wit: G1Affine = rng.gen();
+        let mut costs = vec![];
+
+        for auto_reduce in vec![false, true].into_iter() {
+            let mut cs = TrivialAssembly::<Bn256, Width4WithCustomGates, SelectorOptimizedWidth4MainGateWithDNext>::new();
+            inscribe_default_bitop_range_table(&mut cs).unwrap();
+            let mut params = RnsParameters::<Bn256, Fq>::new_optimal(&mut cs, 80usize);
+            params.set_auto_reduce(auto_reduce);
+
+            let a = AffinePoint::alloc(&mut cs, Some(a_wit), &params).unwrap();
+            let b = AffinePoint::alloc(&mut cs, Some(b_wit), &params).unwrap();
+            let counter_start = cs.get_current_step_number();
+            let sum = a.add_unequal_unchecked(&mut cs, &b).unwrap();
+            let doubled = sum.double(&mut cs).unwrap();
+            costs.push(cs.get_current_step_number() - counter_start);
+
+            for coord in [&sum.x, &sum.y, &doubled.x, &doubled.y].iter() {
+                let is_normalized = coord.get_reduction_status() == ReductionStatus::Normalized;
+                assert!(!auto_reduce || is_normalized);
+            }
+            assert!(cs.is_satisfied());
+        }
+
+        println!("add and double: {} gates without auto reduction, {} gates with", costs[0], costs[1]);
+        assert!(costs[0] < costs[1]);
+    }
+
+    #[test]
+    fn test_arithmetic_for_pallas_curve() {
+        use super::super::pallas::fq::Fq as PallasFq;
+        use super::super::pallas::fr::Fr as PallasFr;
+        use super::super::pallas::PointAffine as PallasG1;
+
+        let mut cs = TrivialAssembly::<Bn256, Width4WithCustomGates, SelectorOptimizedWidth4MainGateWithDNext>::new();
+        inscribe_default_bitop_range_table(&mut cs).unwrap();
+        let params = RnsParameters::<Bn256, PallasFq>::new_optimal(&mut cs, 64usize);
+        let scalar_params = RnsParameters::<Bn256, PallasFr>::new_optimal(&mut cs, 80usize);
+        let mut rng = rand::thread_rng();
+
+        let a_wit: PallasG1 = rng.gen();
+        let b_wit: PallasG1 = rng.gen();
+        let mut sum_wit = a_wit.into_projective();
+        sum_wit.add_assign_mixed(&b_wit);
+        let mut double_wit = a_wit.into_projective();
+        double_wit.double();
+        let scalar_wit : PallasFr = rng.gen();
+        let mut tmp = a_wit.into_projective();
+        tmp.mul_assign(scalar_wit);
         let result = tmp.into_affine();
         
-        let mut a = AffinePoint::alloc(&mut cs, Some(a), &params).unwrap();
-        let mut scalar = FieldElement::alloc(&mut cs, Some(scalar), &scalar_params).unwrap();
+        let mut a = AffinePoint::alloc(&mut cs, Some(a_wit), &params).unwrap();
+        let mut b = AffinePoint::alloc(&mut cs, Some(b_wit), &params).unwrap();
+        let mut expected_sum = AffinePoint::alloc(&mut cs, Some(sum_wit.into_affine()), &params).unwrap();
+        let mut expected_double = AffinePoint::alloc(&mut cs, Some(double_wit.into_affine()), &params).unwrap();
+        let mut scalar = FieldElement::alloc(&mut cs, Some(scalar_wit), &scalar_params).unwrap();
         let mut actual_result = AffinePoint::alloc(&mut cs, Some(result), &params).unwrap();
+
+        let mut sum = a.add_unequal(&mut cs, &mut b).unwrap();
+        AffinePoint::enforce_equal(&mut cs, &mut sum, &mut expected_sum).unwrap();
+        let mut doubled = a.double(&mut cs).unwrap();
+        AffinePoint::enforce_equal(&mut cs, &mut doubled, &mut expected_double).unwrap();
+
         let naive_mul_start = cs.get_current_step_number();
         let mut result = a.mul_by_scalar_for_prime_order_curve(&mut cs, &mut scalar).unwrap();
         let mut result = unsafe { result.convert_to_affine(&mut cs).unwrap() };
         let naive_mul_end = cs.get_current_step_number();
         println!("num of gates: {}", naive_mul_end - naive_mul_start);
 
-        // println!("actual result: x: {}, y: {}", actual_result.x.get_field_value().unwrap(), actual_result.y.get_field_value().unwrap());
-        // println!("computed result: x: {}, y: {}", result.x.get_field_value().unwrap(), result.y.get_field_value().unwrap());
-
         AffinePoint::enforce_equal(&mut cs, &mut result, &mut actual_result).unwrap();
         assert!(cs.is_satisfied()); 
-        println!("SCALAR MULTIPLICATION final");
     }
-}
 
+    #[test]
+    fn test_arithmetic_for_vesta_curve() {
+        use super::super::vesta::fq::Fq as VestaFq;
+        use super::super::vesta::fr::Fr as VestaFr;
+        use super::super::vesta::PointAffine as VestaG1;
+
+        let mut cs = TrivialAssembly::<Bn256, Width4WithCustomGates, SelectorOptimizedWidth4MainGateWithDNext>::new();
+        inscribe_default_bitop_range_table(&mut cs).unwrap();
+        let params = RnsParameters::<Bn256, VestaFq>::new_optimal(&mut cs, 64usize);
+        let scalar_params = RnsParameters::<Bn256, VestaFr>::new_optimal(&mut cs, 80usize);
+        let mut rng = rand::thread_rng();
+
+        let a_wit: VestaG1 = rng.gen();
+        let b_wit: VestaG1 = rng.gen();
+        let mut sum_wit = a_wit.into_projective();
+        sum_wit.add_assign_mixed(&b_wit);
+        let mut double_wit = a_wit.into_projective();
+        double_wit.double();
+        let scalar_wit : VestaFr = rng.gen();
+        let mut tmp = a_wit.into_projective();
+        tmp.mul_assign(scalar_wit);
+        let result = tmp.into_affine();
+        
+        let mut a = AffinePoint::alloc(&mut cs, Some(a_wit), &params).unwrap();
+        let mut b = AffinePoint::alloc(&mut cs, Some(b_wit), &params).unwrap();
+        let mut expected_sum = AffinePoint::alloc(&mut cs, Some(sum_wit.into_affine()), &params).unwrap();
+        let mut expected_double = AffinePoint::alloc(&mut cs, Some(double_wit.into_affine()), &params).unwrap();
+        let mut scalar = FieldElement::alloc(&mut cs, Some(scalar_wit), &scalar_params).unwrap();
+        let mut actual_result = AffinePoint::alloc(&mut cs, Some(result), &params).unwrap();
+
+        let mut sum = a.add_unequal(&mut cs, &mut b).unwrap();
+        AffinePoint::enforce_equal(&mut cs, &mut sum, &mut expected_sum).unwrap();
+        let mut doubled = a.double(&mut cs).unwrap();
+        AffinePoint::enforce_equal(&mut cs, &mut doubled, &mut expected_double).unwrap();
+
+        let naive_mul_start = cs.get_current_step_number();
+        let mut result = a.mul_by_scalar_for_prime_order_curve(&mut cs, &mut scalar).unwrap();
+        let mut result = unsafe { result.convert_to_affine(&mut cs).unwrap() };
+        let naive_mul_end = cs.get_current_step_number();
+        println!("num of gates: {}", naive_mul_end - naive_mul_start);
 
+        AffinePoint::enforce_equal(&mut cs, &mut result, &mut actual_result).unwrap();
+        assert!(cs.is_satisfied()); 
+    }
+}
@@ -59,7 +59,11 @@ where <G as GenericCurveAffine>::Base: PrimeField
 {
     fn from(affine_pt: AffinePoint<'a, E, G>) -> Self {
         let params = affine_pt.x.representation_params;
-        let AffinePoint { x, y, value} = affine_pt;
+        // complete formulas expect the point at infinity to be (0: 1: 0), so the conversion is only valid for regular points
+        if let Some(flag) = affine_pt.is_infinity.get_value() {
+            debug_assert!(!flag, "point at infinity can't be converted into projective form directly");
+        }
+        let AffinePoint { x, y, value, .. } = affine_pt;
 
         ProjectivePoint::<E, G> {
             x, y,
@@ -95,6 +99,22 @@ impl<'a, E: Engine, G: GenericCurveAffine> ProjectivePoint<'a, E, G> where <G as
         Self { x, y, z, value }
     }
 
+    // unlike From this respects the infinity flag: the point at infinity is mapped to (0: 1: 0) as expected
+    // by the complete formulas, which costs three selections if the flag is not known at synthesis time
+    pub fn from_affine<CS: ConstraintSystem<E>>(
+        cs: &mut CS, affine_pt: &AffinePoint<'a, E, G>
+    ) -> Result<Self, SynthesisError> {
+        let params = affine_pt.x.representation_params;
+        match affine_pt.is_infinity {
+            Boolean::Constant(true) => return Ok(Self::zero(params)),
+            Boolean::Constant(false) => return Ok(Self::from(affine_pt.clone())),
+            _ => {},
+        }
+        let mut regular = affine_pt.clone();
+        regular.is_infinity = Boolean::constant(false);
+        Self::conditionally_select(cs, &affine_pt.is_infinity, &Self::zero(params), &Self::from(regular))
+    }
+
     pub fn is_constant(&self) -> bool {
         self.x.is_constant() & self.y.is_constant() & self.z.is_constant()
     }
@@ -132,7 +152,26 @@ impl<'a, E: Engine, G: GenericCurveAffine> ProjectivePoint<'a, E, G> where <G as
         let y = self.y.div(cs, &self.z)?;
         let value = self.get_value();
 
-        Ok(AffinePoint { x, y, value })
+        Ok(AffinePoint { x, y, is_infinity: Boolean::constant(false), value })
+    }
+
+    // safe counterpart of convert_to_affine for untrusted inputs: z != 0 is enforced in the circuit before the
+    // division, so the prover can't pass the point at infinity (or an arbitrary triple with z = 0) for a regular one
+    pub fn convert_to_affine_checked<CS>(&mut self, cs: &mut CS) -> Result<AffinePoint<'a, E, G>, SynthesisError> 
+    where CS: ConstraintSystem<E> {
+        let params = self.x.representation_params;
+        let z_is_zero = self.z.get_field_value().map(|z| z.is_zero()).unwrap_or(false);
+        if self.z.is_constant() && z_is_zero {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        let mut zero = FieldElement::zero(params);
+        FieldElement::enforce_not_equal(cs, &mut self.z, &mut zero)?;
+        // the constraint above is already unsatisfiable for z = 0, we only bail out here
+        // because the division below can't produce a witness for such z
+        if z_is_zero {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        unsafe { self.convert_to_affine(cs) }
     }
 
     pub fn convert_to_affine_or_default<CS: ConstraintSystem<E>>(
@@ -151,14 +190,65 @@ impl<'a, E: Engine, G: GenericCurveAffine> ProjectivePoint<'a, E, G> where <G as
             _ => None,
         };
 
-        let new = AffinePoint { x, y, value };
+        let is_infinity = Boolean::conditionally_select(
+            cs, &is_point_at_infty, &default.is_infinity, &Boolean::constant(false)
+        )?;
+
+        let new = AffinePoint { x, y, is_infinity, value };
         Ok((new, is_point_at_infty))
     }
 
+    // converts all the points to affine form with a single inversion (Montgomery's trick): for prefix products
+    // p_i = z_0 * ... * z_i we compute inv = p_{n-1}^{-1} and then, going backwards, z_i^{-1} = inv * p_{i-1} and 
+    // inv <- inv * z_i, so that n points cost one inversion and 3(n-1) multiplications instead of n inversions,
+    // followed by 2n multiplications for the coordinates. The inversion of the total product enforces that all 
+    // the z coordinates are nonzero, so unlike convert_to_affine this is safe for untrusted inputs as well
+    pub fn batch_into_affine<CS: ConstraintSystem<E>>(
+        cs: &mut CS, points: &[Self]
+    ) -> Result<Vec<AffinePoint<'a, E, G>>, SynthesisError> {
+        if points.is_empty() {
+            return Ok(vec![]);
+        }
+        for point in points.iter() {
+            if let Some(z) = point.z.get_field_value() {
+                if z.is_zero() {
+                    return Err(SynthesisError::Unsatisfiable);
+                }
+            }
+        }
+
+        let mut prefix_products = Vec::with_capacity(points.len());
+        prefix_products.push(points[0].z.clone());
+        for point in points[1..].iter() {
+            let next = prefix_products.last().unwrap().mul(cs, &point.z)?;
+            prefix_products.push(next);
+        }
+
+        let mut inv = prefix_products.last().unwrap().inverse(cs)?;
+        let mut z_inverses = vec![None; points.len()];
+        for i in (1..points.len()).rev() {
+            z_inverses[i] = Some(inv.mul(cs, &prefix_products[i - 1])?);
+            inv = inv.mul(cs, &points[i].z)?;
+        }
+        z_inverses[0] = Some(inv);
+
+        let mut result = Vec::with_capacity(points.len());
+        for (point, z_inv) in points.iter().zip(z_inverses.into_iter()) {
+            let z_inv = z_inv.unwrap();
+            let x = point.x.mul(cs, &z_inv)?;
+            let y = point.y.mul(cs, &z_inv)?;
+            result.push(AffinePoint { x, y, is_infinity: Boolean::constant(false), value: point.get_value() });
+        }
+
+        Ok(result)
+    }
+
     #[track_caller]
     pub fn add<CS: ConstraintSystem<E>>(&self, cs: &mut CS, other: &Self) -> Result<Self, SynthesisError> {
-        // this formula is only valid for curve with zero j-ivariant
-        assert!(G::a_coeff().is_zero());
+        // this formula is only valid for curve with zero j-ivariant, other curves go through the general one
+        if !G::a_coeff().is_zero() {
+            return self.add_for_general_a(cs, other);
+        }
 
         let params = self.x.representation_params;
         let curve_b =  G::b_coeff();
@@ -265,8 +355,10 @@ impl<'a, E: Engine, G: GenericCurveAffine> ProjectivePoint<'a, E, G> where <G as
 
     #[track_caller]
     pub fn double<CS: ConstraintSystem<E>>(&self, cs: &mut CS) -> Result<Self, SynthesisError> {
-        // this formula is only valid for curve with zero j-ivariant
-        assert!(G::a_coeff().is_zero());
+        // this formula is only valid for curve with zero j-ivariant, other curves go through the general one
+        if !G::a_coeff().is_zero() {
+            return self.double_for_general_a(cs);
+        }
 
         let params = self.x.representation_params;
         let curve_b =  G::b_coeff();
@@ -336,8 +428,10 @@ impl<'a, E: Engine, G: GenericCurveAffine> ProjectivePoint<'a, E, G> where <G as
     pub fn add_mixed<CS>(&self, cs: &mut CS, other: &AffinePoint<'a, E, G>) -> Result<Self, SynthesisError> 
     where CS: ConstraintSystem<E>
     {
-        // this formula is only valid for curve with zero j-ivariant
-        assert!(G::a_coeff().is_zero());
+        // this formula is only valid for curve with zero j-ivariant, other curves go through the general one
+        if !G::a_coeff().is_zero() {
+            return self.add_mixed_for_general_a(cs, other);
+        }
         
         let params = self.x.representation_params;
         let curve_b = G::b_coeff();
@@ -423,6 +517,286 @@ impl<'a, E: Engine, G: GenericCurveAffine> ProjectivePoint<'a, E, G> where <G as
         Ok(new)
     }
 
+    fn curve_constants_for_general_a(
+        params: &'a RnsParameters<E, G::Base>
+    ) -> (FieldElement<'a, E, G::Base>, FieldElement<'a, E, G::Base>) {
+        let curve_b = G::b_coeff();
+        let mut curve_b3 = curve_b;
+        curve_b3.double();
+        curve_b3.add_assign(&curve_b);
+        (FieldElement::constant(G::a_coeff(), params), FieldElement::constant(curve_b3, params))
+    }
+
+    // complete addition for arbitrary a (e.g. secp256r1 with a = -3): 
+    // algorithm 1 from https://eprint.iacr.org/2015/1060.pdf
+    #[track_caller]
+    fn add_for_general_a<CS: ConstraintSystem<E>>(&self, cs: &mut CS, other: &Self) -> Result<Self, SynthesisError> {
+        let params = self.x.representation_params;
+        let (a, b3) = Self::curve_constants_for_general_a(params);
+        let (x1, y1, z1) = (&self.x, &self.y, &self.z);
+        let (x2, y2, z2) = (&other.x, &other.y, &other.z);
+
+        // 1. t0 ← X1 · X2 
+        let t0 = x1.mul(cs, x2)?;
+        // 2. t1 ← Y1 · Y2 
+        let t1 = y1.mul(cs, y2)?;
+        // 3. t2 ← Z1 · Z2
+        let t2 = z1.mul(cs, z2)?;
+        // 4. t3 ← X1 + Y1 
+        let t3 = x1.add(cs, y1)?;
+        // 5. t4 ← X2 + Y2 
+        let t4 = x2.add(cs, y2)?;
+        // 6. t3 ← t3 · t4
+        let t3 = t3.mul(cs, &t4)?;
+        // 7. t4 ← t0 + t1 
+        let t4 = t0.add(cs, &t1)?;
+        // 8. t3 ← t3 − t4 
+        let t3 = t3.sub(cs, &t4)?;
+        // 9. t4 ← X1 + Z1
+        let t4 = x1.add(cs, z1)?;
+        // 10. t5 ← X2 + Z2 
+        let t5 = x2.add(cs, z2)?;
+        // 11. t4 ← t4 · t5 
+        let t4 = t4.mul(cs, &t5)?;
+        // 12. t5 ← t0 + t2
+        let t5 = t0.add(cs, &t2)?;
+        // 13. t4 ← t4 − t5 
+        let t4 = t4.sub(cs, &t5)?;
+        // 14. t5 ← Y1 + Z1 
+        let t5 = y1.add(cs, z1)?;
+        // 15. X3 ← Y2 + Z2
+        let x3 = y2.add(cs, z2)?;
+        // 16. t5 ← t5 · X3 
+        let t5 = t5.mul(cs, &x3)?;
+        // 17. X3 ← t1 + t2 
+        let x3 = t1.add(cs, &t2)?;
+        // 18. t5 ← t5 − X3
+        let t5 = t5.sub(cs, &x3)?;
+        // 19. Z3 ← a · t4 
+        let z3 = a.mul(cs, &t4)?;
+        // 20. X3 ← b3 · t2 
+        let x3 = b3.mul(cs, &t2)?;
+        // 21. Z3 ← X3 + Z3
+        let z3 = x3.add(cs, &z3)?;
+        // 22. X3 ← t1 − Z3 
+        let x3 = t1.sub(cs, &z3)?;
+        // 23. Z3 ← t1 + Z3 
+        let z3 = t1.add(cs, &z3)?;
+        // 24. Y3 ← X3 · Z3
+        let y3 = x3.mul(cs, &z3)?;
+        // 25. t1 ← t0 + t0 
+        let t1 = t0.double(cs)?;
+        // 26. t1 ← t1 + t0 
+        let t1 = t1.add(cs, &t0)?;
+        // 27. t2 ← a · t2
+        let t2 = a.mul(cs, &t2)?;
+        // 28. t4 ← b3 · t4 
+        let t4 = b3.mul(cs, &t4)?;
+        // 29. t1 ← t1 + t2 
+        let t1 = t1.add(cs, &t2)?;
+        // 30. t2 ← t0 − t2
+        let t2 = t0.sub(cs, &t2)?;
+        // 31. t2 ← a · t2 
+        let t2 = a.mul(cs, &t2)?;
+        // 32. t4 ← t4 + t2 
+        let t4 = t4.add(cs, &t2)?;
+        // 33. t0 ← t1 · t4
+        let t0 = t1.mul(cs, &t4)?;
+        // 34. Y3 ← Y3 + t0 
+        let y3 = y3.add(cs, &t0)?;
+        // 35. t0 ← t5 · t4 
+        let t0 = t5.mul(cs, &t4)?;
+        // 36. X3 ← t3 · X3
+        let x3 = t3.mul(cs, &x3)?;
+        // 37. X3 ← X3 − t0 
+        let x3 = x3.sub(cs, &t0)?;
+        // 38. t0 ← t3 · t1 
+        let t0 = t3.mul(cs, &t1)?;
+        // 39. Z3 ← t5 · Z3
+        let z3 = t5.mul(cs, &z3)?;
+        // 40. Z3 ← Z3 + t0
+        let z3 = z3.add(cs, &t0)?;
+
+        let new_value = match (self.value, other.value) {
+            (Some(this), Some(other)) => {
+                let mut tmp = this;
+                tmp.add_assign(&other);
+                Some(tmp)
+            },
+            _ => None
+        };
+
+        Ok(Self { x: x3, y: y3, z: z3, value: new_value })
+    }
+
+    // algorithm 3 from https://eprint.iacr.org/2015/1060.pdf
+    #[track_caller]
+    fn double_for_general_a<CS: ConstraintSystem<E>>(&self, cs: &mut CS) -> Result<Self, SynthesisError> {
+        let params = self.x.representation_params;
+        let (a, b3) = Self::curve_constants_for_general_a(params);
+        let (x, y, z) = (&self.x, &self.y, &self.z);
+
+        // 1. t0 ← X · X 
+        let t0 = x.square(cs)?;
+        // 2. t1 ← Y · Y 
+        let t1 = y.square(cs)?;
+        // 3. t2 ← Z · Z
+        let t2 = z.square(cs)?;
+        // 4. t3 ← X · Y 
+        let t3 = x.mul(cs, y)?;
+        // 5. t3 ← t3 + t3 
+        let t3 = t3.double(cs)?;
+        // 6. Z3 ← X · Z
+        let z3 = x.mul(cs, z)?;
+        // 7. Z3 ← Z3 + Z3 
+        let z3 = z3.double(cs)?;
+        // 8. X3 ← a · Z3 
+        let x3 = a.mul(cs, &z3)?;
+        // 9. Y3 ← b3 · t2
+        let y3 = b3.mul(cs, &t2)?;
+        // 10. Y3 ← X3 + Y3 
+        let y3 = x3.add(cs, &y3)?;
+        // 11. X3 ← t1 − Y3 
+        let x3 = t1.sub(cs, &y3)?;
+        // 12. Y3 ← t1 + Y3
+        let y3 = t1.add(cs, &y3)?;
+        // 13. Y3 ← X3 · Y3 
+        let y3 = x3.mul(cs, &y3)?;
+        // 14. X3 ← t3 · X3 
+        let x3 = t3.mul(cs, &x3)?;
+        // 15. Z3 ← b3 · Z3
+        let z3 = b3.mul(cs, &z3)?;
+        // 16. t2 ← a · t2 
+        let t2 = a.mul(cs, &t2)?;
+        // 17. t3 ← t0 − t2 
+        let t3 = t0.sub(cs, &t2)?;
+        // 18. t3 ← a · t3
+        let t3 = a.mul(cs, &t3)?;
+        // 19. t3 ← t3 + Z3 
+        let t3 = t3.add(cs, &z3)?;
+        // 20. Z3 ← t0 + t0 
+        let z3 = t0.double(cs)?;
+        // 21. t0 ← Z3 + t0
+        let t0 = z3.add(cs, &t0)?;
+        // 22. t0 ← t0 + t2 
+        let t0 = t0.add(cs, &t2)?;
+        // 23. t0 ← t0 · t3 
+        let t0 = t0.mul(cs, &t3)?;
+        // 24. Y3 ← Y3 + t0
+        let y3 = y3.add(cs, &t0)?;
+        // 25. t2 ← Y · Z 
+        let t2 = y.mul(cs, z)?;
+        // 26. t2 ← t2 + t2 
+        let t2 = t2.double(cs)?;
+        // 27. t0 ← t2 · t3
+        let t0 = t2.mul(cs, &t3)?;
+        // 28. X3 ← X3 − t0 
+        let x3 = x3.sub(cs, &t0)?;
+        // 29. Z3 ← t2 · t1 
+        let z3 = t2.mul(cs, &t1)?;
+        // 30. Z3 ← Z3 + Z3
+        let z3 = z3.double(cs)?;
+        // 31. Z3 ← Z3 + Z3
+        let z3 = z3.double(cs)?;
+
+        let new_value = self.value.map(|el| {
+            let mut tmp = el;
+            tmp.double();
+            tmp
+        });
+
+        Ok(Self { x: x3, y: y3, z: z3, value: new_value })
+    }
+
+    // algorithm 2 from https://eprint.iacr.org/2015/1060.pdf
+    #[track_caller]
+    fn add_mixed_for_general_a<CS>(&self, cs: &mut CS, other: &AffinePoint<'a, E, G>) -> Result<Self, SynthesisError> 
+    where CS: ConstraintSystem<E>
+    {
+        let params = self.x.representation_params;
+        let (a, b3) = Self::curve_constants_for_general_a(params);
+        let (x1, y1, z1) = (&self.x, &self.y, &self.z);
+        let (x2, y2) = (&other.x, &other.y);
+
+        // 1. t0 ← X1 · X2 
+        let t0 = x1.mul(cs, x2)?;
+        // 2. t1 ← Y1 · Y2 
+        let t1 = y1.mul(cs, y2)?;
+        // 3. t3 ← X2 + Y2
+        let t3 = x2.add(cs, y2)?;
+        // 4. t4 ← X1 + Y1 
+        let t4 = x1.add(cs, y1)?;
+        // 5. t3 ← t3 · t4 
+        let t3 = t3.mul(cs, &t4)?;
+        // 6. t4 ← t0 + t1
+        let t4 = t0.add(cs, &t1)?;
+        // 7. t3 ← t3 − t4 
+        let t3 = t3.sub(cs, &t4)?;
+        // 8. t4 ← X2 · Z1 
+        let t4 = x2.mul(cs, z1)?;
+        // 9. t4 ← t4 + X1
+        let t4 = t4.add(cs, x1)?;
+        // 10. t5 ← Y2 · Z1 
+        let t5 = y2.mul(cs, z1)?;
+        // 11. t5 ← t5 + Y1 
+        let t5 = t5.add(cs, y1)?;
+        // 12. Z3 ← a · t4
+        let z3 = a.mul(cs, &t4)?;
+        // 13. X3 ← b3 · Z1 
+        let x3 = b3.mul(cs, z1)?;
+        // 14. Z3 ← X3 + Z3 
+        let z3 = x3.add(cs, &z3)?;
+        // 15. X3 ← t1 − Z3
+        let x3 = t1.sub(cs, &z3)?;
+        // 16. Z3 ← t1 + Z3 
+        let z3 = t1.add(cs, &z3)?;
+        // 17. Y3 ← X3 · Z3 
+        let y3 = x3.mul(cs, &z3)?;
+        // 18. t1 ← t0 + t0
+        let t1 = t0.double(cs)?;
+        // 19. t1 ← t1 + t0 
+        let t1 = t1.add(cs, &t0)?;
+        // 20. t2 ← a · Z1 
+        let t2 = a.mul(cs, z1)?;
+        // 21. t4 ← b3 · t4
+        let t4 = b3.mul(cs, &t4)?;
+        // 22. t1 ← t1 + t2 
+        let t1 = t1.add(cs, &t2)?;
+        // 23. t2 ← t0 − t2 
+        let t2 = t0.sub(cs, &t2)?;
+        // 24. t2 ← a · t2
+        let t2 = a.mul(cs, &t2)?;
+        // 25. t4 ← t4 + t2 
+        let t4 = t4.add(cs, &t2)?;
+        // 26. t0 ← t1 · t4 
+        let t0 = t1.mul(cs, &t4)?;
+        // 27. Y3 ← Y3 + t0
+        let y3 = y3.add(cs, &t0)?;
+        // 28. t0 ← t5 · t4 
+        let t0 = t5.mul(cs, &t4)?;
+        // 29. X3 ← t3 · X3 
+        let x3 = t3.mul(cs, &x3)?;
+        // 30. X3 ← X3 − t0
+        let x3 = x3.sub(cs, &t0)?;
+        // 31. t0 ← t3 · t1 
+        let t0 = t3.mul(cs, &t1)?;
+        // 32. Z3 ← t5 · Z3 
+        let z3 = t5.mul(cs, &z3)?;
+        // 33. Z3 ← Z3 + t0
+        let z3 = z3.add(cs, &t0)?;
+
+        let new_value = match (self.value, other.get_value()) {
+            (Some(this), Some(other)) => {
+                let mut tmp = this;
+                tmp.add_assign_mixed(&other);
+                Some(tmp)
+            },
+            _ => None
+        };
+
+        Ok(Self { x: x3, y: y3, z: z3, value: new_value })
+    }
+
     pub fn conditionally_select<CS: ConstraintSystem<E>>(
         cs: &mut CS, flag: &Boolean, first: &Self, second: &Self
     ) -> Result<Self, SynthesisError> {
@@ -442,8 +816,52 @@ impl<'a, E: Engine, G: GenericCurveAffine> ProjectivePoint<'a, E, G> where <G as
         let selected = Self { x, y, z, value };
         Ok(selected)
     }
-}
 
+    // sum of [scalars[i]] * points[i] with the joint bit scan starting from the most significant bit: 
+    // all the computations are done in projective coordinates, so no inversions are required at all
+    pub fn multiexp<CS: ConstraintSystem<E>>(
+        cs: &mut CS, points: &[Self], scalars: &mut [FieldElement<'a, E, G::Scalar>]
+    ) -> Result<Self, SynthesisError> {
+        if scalars.len() != points.len() || points.is_empty() {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        let params = points[0].x.representation_params;
+
+        let mut terms = Vec::with_capacity(scalars.len());
+        for (scalar, point) in scalars.iter_mut().zip(points.iter()) {
+            if scalar.is_constant() && scalar.get_field_value().unwrap().is_zero() {
+                continue;
+            }
+            terms.push((point, scalar.decompose_into_binary_representation(cs)?));
+        }
+        Self::joint_bit_scan(cs, &terms, params, |cs, acc, point| acc.add(cs, point))
+    }
+
+    // double-and-add over the little-endian bit decompositions of all the scalars at once, 
+    // add is used to add the point of the term to the accumulator
+    pub(crate) fn joint_bit_scan<CS, P, F>(
+        cs: &mut CS, terms: &[(&P, Vec<Boolean>)], params: &'a RnsParameters<E, G::Base>, mut add: F
+    ) -> Result<Self, SynthesisError> 
+    where CS: ConstraintSystem<E>, F: FnMut(&mut CS, &Self, &P) -> Result<Self, SynthesisError>
+    {
+        if terms.is_empty() {
+            return Ok(Self::zero(params));
+        }
+        let num_bits = terms[0].1.len();
+        assert!(terms.iter().all(|(_, x)| x.len() == num_bits));
+
+        let mut acc = Self::zero(params);
+        for bit_idx in (0..num_bits).rev() {
+            acc = acc.double(cs)?;
+            for (point, decomposition) in terms.iter() {
+                let added = add(cs, &acc, *point)?;
+                acc = Self::conditionally_select(cs, &decomposition[bit_idx], &added, &acc)?;
+            }
+        }
+
+        Ok(acc)
+    }
+}
 
 #[cfg(test)]
 mod test {
@@ -485,6 +903,151 @@ mod test {
         assert!(cs.is_satisfied()); 
         println!("PROJ MIXED ADD 2");
     }
-}
 
-    
\ No newline at end of file
+    #[test]
+    fn test_convert_to_affine_checked() {
+        let mut cs = TrivialAssembly::<Bn256, Width4WithCustomGates, SelectorOptimizedWidth4MainGateWithDNext>::new();
+        inscribe_default_bitop_range_table(&mut cs).unwrap();
+        let params = RnsParameters::<Bn256, Fq>::new_optimal(&mut cs, 80usize);
+        let mut rng = rand::thread_rng();
+
+        let a: G1Affine = rng.gen();
+        let mut doubled_wit = a.into_projective();
+        doubled_wit.double();
+        let a_alloc = AffinePoint::alloc(&mut cs, Some(a), &params).unwrap();
+        let a_projective = ProjectivePoint::from(a_alloc);
+        let mut doubled = a_projective.double(&mut cs).unwrap();
+        let mut doubled = doubled.convert_to_affine_checked(&mut cs).unwrap();
+        let mut actual = AffinePoint::alloc(&mut cs, Some(doubled_wit.into_affine()), &params).unwrap();
+        AffinePoint::enforce_equal(&mut cs, &mut doubled, &mut actual).unwrap();
+        assert!(cs.is_satisfied());
+
+        // z = 0 is rejected both for constant and for allocated coordinates
+        let mut zero = ProjectivePoint::<Bn256, G1Affine>::zero(&params);
+        assert!(zero.convert_to_affine_checked(&mut cs).is_err());
+        let mut malicious = a_projective.clone();
+        malicious.z = FieldElement::alloc(&mut cs, Some(Fq::zero()), &params).unwrap();
+        assert!(malicious.convert_to_affine_checked(&mut cs).is_err());
+
+        // the rejection of allocated z = 0 does not rely on the witness check only:
+        // the constraint z != 0 is already in the circuit and can't be satisfied
+        let mut cs = TrivialAssembly::<Bn256, Width4WithCustomGates, SelectorOptimizedWidth4MainGateWithDNext>::new();
+        inscribe_default_bitop_range_table(&mut cs).unwrap();
+        let params = RnsParameters::<Bn256, Fq>::new_optimal(&mut cs, 80usize);
+        let a_alloc = AffinePoint::alloc(&mut cs, Some(a), &params).unwrap();
+        let mut malicious = ProjectivePoint::from(a_alloc);
+        malicious.z = FieldElement::alloc(&mut cs, Some(Fq::zero()), &params).unwrap();
+        assert!(cs.is_satisfied());
+        assert!(malicious.convert_to_affine_checked(&mut cs).is_err());
+        assert!(!cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_from_affine_respects_infinity_flag() {
+        let mut cs = TrivialAssembly::<Bn256, Width4WithCustomGates, SelectorOptimizedWidth4MainGateWithDNext>::new();
+        inscribe_default_bitop_range_table(&mut cs).unwrap();
+        let params = RnsParameters::<Bn256, Fq>::new_optimal(&mut cs, 80usize);
+        let scalar_params = RnsParameters::<Bn256, Fr>::new_optimal(&mut cs, 80usize);
+        let mut rng = rand::thread_rng();
+
+        let a: G1Affine = rng.gen();
+        for flag in vec![false, true].into_iter() {
+            let mut a_alloc = AffinePoint::alloc(&mut cs, Some(a), &params).unwrap();
+            a_alloc.is_infinity = Boolean::from(AllocatedBit::alloc(&mut cs, Some(flag)).unwrap());
+            if flag {
+                a_alloc.value = Some(G1Affine::zero());
+            }
+            let converted = ProjectivePoint::from_affine(&mut cs, &a_alloc).unwrap();
+            assert_eq!(converted.z.get_field_value().unwrap().is_zero(), flag);
+            assert_eq!(converted.get_value(), a_alloc.get_value());
+
+            // mixed additions of the scalar multiplication are fed by the regular point
+            let mut scalar = FieldElement::alloc(&mut cs, Some(rng.gen()), &scalar_params).unwrap();
+            let res = a_alloc.mul_by_scalar_for_prime_order_curve(&mut cs, &mut scalar).unwrap();
+            assert_eq!(res.z.get_field_value().unwrap().is_zero(), flag);
+        }
+        assert!(cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_batch_into_affine() {
+        let mut cs = TrivialAssembly::<Bn256, Width4WithCustomGates, SelectorOptimizedWidth4MainGateWithDNext>::new();
+        inscribe_default_bitop_range_table(&mut cs).unwrap();
+        let params = RnsParameters::<Bn256, Fq>::new_optimal(&mut cs, 80usize);
+        let mut rng = rand::thread_rng();
+
+        // random multiples of the same point, so that z coordinates are nontrivial
+        let num_points = 8;
+        let base_wit: G1Affine = rng.gen();
+        let base = ProjectivePoint::from(AffinePoint::alloc(&mut cs, Some(base_wit), &params).unwrap());
+        let mut points = Vec::with_capacity(num_points);
+        let mut acc = base.clone();
+        for _ in 0..num_points {
+            acc = acc.double(&mut cs).unwrap();
+            if rng.gen() {
+                acc = acc.add(&mut cs, &base).unwrap();
+            }
+            points.push(acc.clone());
+        }
+
+        let n = cs.get_current_step_number();
+        let per_point: Vec<_> = points.iter().map(|p| unsafe { p.convert_to_affine(&mut cs).unwrap() }).collect();
+        let per_point_gates = cs.get_current_step_number() - n;
+
+        let n = cs.get_current_step_number();
+        let batched = ProjectivePoint::batch_into_affine(&mut cs, &points).unwrap();
+        let batched_gates = cs.get_current_step_number() - n;
+        println!("num of gates for {} points: {} (per point conversion: {})", num_points, batched_gates, per_point_gates);
+
+        for (mut a, mut b) in batched.into_iter().zip(per_point.into_iter()) {
+            assert_eq!(a.get_value(), b.get_value());
+            assert_eq!(a.x.get_field_value(), b.x.get_field_value());
+            assert_eq!(a.y.get_field_value(), b.y.get_field_value());
+            AffinePoint::enforce_equal(&mut cs, &mut a, &mut b).unwrap();
+        }
+        assert!(cs.is_satisfied());
+
+        let mut malicious = points.clone();
+        malicious[3].z = FieldElement::alloc(&mut cs, Some(Fq::zero()), &params).unwrap();
+        assert!(ProjectivePoint::batch_into_affine(&mut cs, &malicious).is_err());
+    }
+
+    #[test]
+    fn test_projective_multiexp() {
+        let mut cs = TrivialAssembly::<Bn256, Width4WithCustomGates, SelectorOptimizedWidth4MainGateWithDNext>::new();
+        inscribe_default_bitop_range_table(&mut cs).unwrap();
+        let params = RnsParameters::<Bn256, Fq>::new_optimal(&mut cs, 80usize);
+        let scalar_params = RnsParameters::<Bn256, Fr>::new_optimal(&mut cs, 80usize);
+        let mut rng = rand::thread_rng();
+
+        let num_points = 3;
+        let mut points = Vec::with_capacity(num_points);
+        let mut scalars = Vec::with_capacity(num_points);
+        let mut expected = G1Affine::zero().into_projective();
+        for _ in 0..num_points {
+            let point: G1Affine = rng.gen();
+            let scalar: Fr = rng.gen();
+            let mut tmp = point.into_projective();
+            tmp.mul_assign(scalar);
+            expected.add_assign(&tmp);
+
+            let point = AffinePoint::alloc(&mut cs, Some(point), &params).unwrap();
+            points.push(ProjectivePoint::from(point));
+            scalars.push(FieldElement::alloc(&mut cs, Some(scalar), &scalar_params).unwrap());
+        }
+        let mut actual_result = AffinePoint::alloc(&mut cs, Some(expected.into_affine()), &params).unwrap();
+
+        let multiexp_start = cs.get_current_step_number();
+        let result = ProjectivePoint::multiexp(&mut cs, &points, &mut scalars).unwrap();
+        let multiexp_end = cs.get_current_step_number();
+        println!("num of gates: {}", multiexp_end - multiexp_start);
+
+        let mut result = unsafe { result.convert_to_affine(&mut cs).unwrap() };
+        AffinePoint::enforce_equal(&mut cs, &mut result, &mut actual_result).unwrap();
+        assert!(cs.is_satisfied());
+
+        // empty and mismatched inputs are rejected instead of panicking
+        assert!(ProjectivePoint::multiexp(&mut cs, &points[..0], &mut scalars[..0]).is_err());
+        assert!(ProjectivePoint::multiexp(&mut cs, &points[..2], &mut scalars).is_err());
+    }
+}
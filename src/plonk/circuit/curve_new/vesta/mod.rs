@@ -0,0 +1,36 @@
+use crate::bellman::pairing::ff::BitIterator;
+use crate::bellman::pairing::{GenericCurveAffine, GenericCurveProjective, GroupDecodingError, EncodingBytes, GenericUncompressedEncodable, GenericCompressedEncodable};
+use crate::bellman::pairing::ff::*;
+use rand::*;
+
+
+pub mod fr;
+pub mod fq;
+
+use self::fr::*;
+use self::fq::*;
+
+short_weierstrass_curve_impl!("Vesta", zero_a);
+
+impl PointAffine {
+    fn get_coeff_a() -> <Self as GenericCurveAffine>::Base {
+        Fq::zero()
+    }
+
+    fn get_coeff_b() -> <Self as GenericCurveAffine>::Base {
+        Fq::from_str("5").unwrap()
+    }
+
+    fn get_generator() -> Self {
+        Self {
+            // generator is (-1, 2)
+            x: {
+                let mut minus_one = Fq::one();
+                minus_one.negate();
+                minus_one
+            },
+            y: Fq::from_str("2").unwrap(),
+            infinity: false
+        }
+    }
+}
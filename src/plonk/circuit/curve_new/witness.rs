@@ -0,0 +1,99 @@
+// serializable witness part of the affine point: coordinates of the underlying native point (if known)
+use super::*;
+use crate::bellman::pairing::{Engine, GenericCurveAffine};
+use crate::bellman::pairing::ff::PrimeField;
+use crate::bellman::SynthesisError;
+use crate::bellman::plonk::better_better_cs::cs::ConstraintSystem;
+use crate::plonk::circuit::bigint_new::*;
+use num_bigint::BigUint;
+use serde::{Serialize, Deserialize};
+
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AffinePointWitness {
+    Unknown,
+    Infinity,
+    Point { x: BigUint, y: BigUint }
+}
+
+impl AffinePointWitness {
+    pub fn from_value<G: GenericCurveAffine>(value: Option<G>) -> Self where <G as GenericCurveAffine>::Base: PrimeField {
+        match value {
+            None => AffinePointWitness::Unknown,
+            Some(v) if v.is_zero() => AffinePointWitness::Infinity,
+            Some(v) => {
+                let (x, y) = v.into_xy_unchecked();
+                AffinePointWitness::Point { x: fe_to_biguint(&x), y: fe_to_biguint(&y) }
+            }
+        }
+    }
+
+    // deserialized data is untrusted: coordinates should be reduced and the point should lie on the curve
+    pub fn to_value<G: GenericCurveAffine>(&self) -> Result<Option<G>, SynthesisError> where <G as GenericCurveAffine>::Base: PrimeField {
+        match self {
+            AffinePointWitness::Unknown => Ok(None),
+            AffinePointWitness::Infinity => Ok(Some(G::zero())),
+            AffinePointWitness::Point { x, y } => {
+                let x = FieldElementWitness { value: Some(x.clone()) }.to_value::<G::Base>()?.unwrap();
+                let y = FieldElementWitness { value: Some(y.clone()) }.to_value::<G::Base>()?.unwrap();
+                let point = G::from_xy_checked(x, y).map_err(|_| SynthesisError::Unsatisfiable)?;
+                Ok(Some(point))
+            }
+        }
+    }
+}
+
+impl<'a, E: Engine, G: GenericCurveAffine> AffinePoint<'a, E, G> where <G as GenericCurveAffine>::Base: PrimeField {
+    pub fn get_witness(&self) -> AffinePointWitness {
+        AffinePointWitness::from_value(self.get_value())
+    }
+
+    // as for alloc, the point at infinity can not be allocated this way: Unsatisfiable error is returned
+    #[track_caller]
+    pub fn alloc_from_witness<CS: ConstraintSystem<E>>(
+        cs: &mut CS, witness: &AffinePointWitness, params: &'a RnsParameters<E, G::Base>
+    ) -> Result<Self, SynthesisError> {
+        let value = witness.to_value::<G>()?;
+        if value.map(|v| v.is_zero()).unwrap_or(false) {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        Self::alloc(cs, value, params)
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::bellman::pairing::bn256::{Fq, Bn256, G1Affine};
+    use crate::plonk::circuit::Width4WithCustomGates;
+    use crate::bellman::plonk::better_better_cs::gates::selector_optimized_with_d_next::SelectorOptimizedWidth4MainGateWithDNext;
+    use crate::bellman::plonk::better_better_cs::cs::*;
+    use rand::Rng;
+
+    #[test]
+    fn test_affine_point_witness_round_trip() {
+        let mut cs = TrivialAssembly::<Bn256, Width4WithCustomGates, SelectorOptimizedWidth4MainGateWithDNext>::new();
+        inscribe_default_bitop_range_table(&mut cs).unwrap();
+        let params = RnsParameters::<Bn256, Fq>::new_optimal(&mut cs, 80usize);
+        let mut rng = rand::thread_rng();
+
+        let p_wit: G1Affine = rng.gen();
+        let p = AffinePoint::alloc(&mut cs, Some(p_wit), &params).unwrap();
+        let encoded = serde_json::to_string(&p.get_witness()).unwrap();
+        let decoded: AffinePointWitness = serde_json::from_str(&encoded).unwrap();
+        let q = AffinePoint::alloc_from_witness(&mut cs, &decoded, &params).unwrap();
+        assert_eq!(q.get_value(), Some(p_wit));
+
+        let infinity = AffinePointWitness::from_value(Some(G1Affine::zero()));
+        let decoded: AffinePointWitness = serde_json::from_str(&serde_json::to_string(&infinity).unwrap()).unwrap();
+        assert_eq!(decoded, AffinePointWitness::Infinity);
+        assert!(decoded.to_value::<G1Affine>().unwrap().unwrap().is_zero());
+
+        // a point which is not on the curve is rejected
+        let (x, y) = p_wit.into_xy_unchecked();
+        let invalid = AffinePointWitness::Point { x: fe_to_biguint(&x), y: fe_to_biguint(&y) + BigUint::from(1u64) };
+        assert!(invalid.to_value::<G1Affine>().is_err());
+        assert!(cs.is_satisfied());
+    }
+}
@@ -48,7 +48,8 @@ impl<E: Engine> std::fmt::Display for Term<E> {
 
 impl<E: Engine> Term<E> {
     pub fn enforce_equal<CS: ConstraintSystem<E>>(&self, cs: &mut CS, other: &Self) -> Result<(), SynthesisError> {
-        if self.is_constant() || other.is_constant() {
+        // if only one of the terms is constant it is folded into the constant term of the linear combination
+        if self.is_constant() && other.is_constant() {
             assert_eq!(self.get_constant_value(), other.get_constant_value());
             return Ok(())
         }
@@ -1054,4 +1055,43 @@ mod test {
         }
 
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_enforce_equal_with_constant(){
+        use rand::{XorShiftRng, SeedableRng, Rng};
+        let rng = &mut XorShiftRng::from_seed([0x3dbe6259, 0x8d313d76, 0x3237db17, 0xe5bc0654]);
+
+        for _ in 0..100 {
+            let v0: Fr = rng.gen();
+            let a0: Fr = rng.gen();
+            let c0: Fr = rng.gen();
+
+            let mut val = a0;
+            val.mul_assign(&v0);
+            val.add_assign(&c0);
+
+            for (expected, must_be_satisfied) in vec![(val, true), (rng.gen(), false)].into_iter() {
+                let mut cs = TrivialAssembly::<Bn256, PlonkCsWidth4WithNextStepParams, Width4MainGateWithDNext>::new();
+
+                let a = AllocatedNum::alloc(
+                    &mut cs,
+                    || {
+                        Ok(a0)
+                    }
+                ).unwrap();
+
+                let mut a_term = Term::<Bn256>::from_allocated_num(a);
+                a_term.scale(&v0);
+                a_term.add_constant(&c0);
+
+                let constant_term = Term::<Bn256>::from_constant(expected);
+
+                // the constant may be on either side, in both cases it must end up in the constraint
+                a_term.enforce_equal(&mut cs, &constant_term).unwrap();
+                constant_term.enforce_equal(&mut cs, &a_term).unwrap();
+
+                assert_eq!(cs.is_satisfied(), must_be_satisfied);
+            }
+        }
+    }
+}